@@ -0,0 +1,52 @@
+// Debris drawn as camera-facing discs, one triangle per object. Object
+// positions, sizes and colors come from a storage buffer that is rewritten
+// every frame; the mesh only carries the triangle corners and the index of
+// the object each vertex belongs to.
+
+#import bevy_pbr::{
+    mesh_view_bindings::view,
+    view_transformations::position_world_to_clip
+}
+
+struct DebrisInstance {
+    // xyz: world position, w: disc radius in world units (0 hides the object).
+    position: vec4<f32>,
+    // Linear RGBA.
+    color: vec4<f32>,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<storage, read> instances: array<DebrisInstance>;
+
+struct Vertex {
+    // Corner of a triangle circumscribing the unit disc.
+    @location(0) corner: vec3<f32>,
+    @location(1) instance_index: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) corner: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    let instance = instances[vertex.instance_index];
+    let right = view.world_from_view[0].xyz;
+    let up = view.world_from_view[1].xyz;
+    let offset = (right * vertex.corner.x + up * vertex.corner.y) * instance.position.w;
+
+    var out: VertexOutput;
+    out.clip_position = position_world_to_clip(instance.position.xyz + offset);
+    out.corner = vertex.corner.xy;
+    out.color = instance.color;
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    if dot(in.corner, in.corner) > 1.0 {
+        discard;
+    }
+    return in.color;
+}
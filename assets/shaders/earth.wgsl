@@ -0,0 +1,39 @@
+// The Earth: standard PBR lighting from the Sun's directional light, plus an
+// emissive night-lights texture that only shows on the unlit side. The
+// texture shares the day texture's UVs.
+
+#import bevy_pbr::{
+    forward_io::{FragmentOutput, VertexOutput},
+    mesh_view_bindings::{lights, view},
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{alpha_discard, apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+
+// Nits at full white; 0 when there is no texture.
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> brightness: f32;
+@group(#{MATERIAL_BIND_GROUP}) @binding(101) var night_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(102) var night_sampler: sampler;
+
+// Width of the twilight band the lights fade in over, in cos(sun angle).
+const TWILIGHT: f32 = 0.1;
+
+@fragment
+fn fragment(in: VertexOutput, @builtin(front_facing) is_front: bool) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+
+#ifdef VERTEX_UVS_A
+    if brightness > 0.0 && lights.n_directional_lights > 0u {
+        let to_sun = lights.directional_lights[0].direction_to_light;
+        let darkness = smoothstep(TWILIGHT, -TWILIGHT, dot(normalize(in.world_normal), to_sun));
+        let city = textureSample(night_texture, night_sampler, in.uv).rgb;
+        out.color = vec4(out.color.rgb + city * brightness * darkness * view.exposure, out.color.a);
+    }
+#endif
+
+    out = main_pass_post_lighting_processing(pbr_input, out);
+    return out;
+}
@@ -0,0 +1,129 @@
+use bevy::{
+    prelude::*,
+    tasks::{ComputeTaskPool, TaskPool},
+};
+use chrono::Utc;
+use std::time::Instant;
+
+use crate::debris::{Debris, DebrisSat, julian_date};
+use crate::instancing::DebrisRendering;
+use crate::propagation::{Propagated, propagate_parallel, propagate_serial};
+use crate::synthetic::synthetic_catalog;
+
+/// Propagation steps timed per pass of the headless benchmark.
+const PROPAGATION_STEPS: usize = 50;
+
+/// Frames rendered after the debris field appears, before timing starts.
+const WARMUP_FRAMES: usize = 120;
+
+/// Time a fixed number of frames once the debris field is populated, log the
+/// result and quit. Started with `--bench-frames`.
+#[derive(Debug, Resource)]
+pub struct FrameBenchmark {
+    pub frames: usize,
+    warmup_left: usize,
+    /// Frame times, ms.
+    samples: Vec<f64>,
+}
+
+impl FrameBenchmark {
+    pub fn new(frames: usize) -> Self {
+        Self {
+            frames,
+            warmup_left: WARMUP_FRAMES,
+            samples: Vec::with_capacity(frames),
+        }
+    }
+}
+
+pub fn run_frame_benchmark(
+    time: Res<Time<Real>>,
+    rendering: Res<DebrisRendering>,
+    mut benchmark: ResMut<FrameBenchmark>,
+    debris: Query<(), With<Debris>>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let objects = debris.iter().count();
+    if objects == 0 {
+        return;
+    }
+    if benchmark.warmup_left > 0 {
+        benchmark.warmup_left -= 1;
+        return;
+    }
+
+    benchmark.samples.push(time.delta_secs_f64() * 1_000.0);
+    if benchmark.samples.len() < benchmark.frames {
+        return;
+    }
+
+    let samples = &mut benchmark.samples;
+    samples.sort_by(f64::total_cmp);
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    let percentile = |p: f64| samples[((samples.len() - 1) as f64 * p).round() as usize];
+    info!(
+        "{:?} rendering, {} objects, {} frames: mean {:.2} ms ({:.0} FPS), median {:.2} ms, p95 {:.2} ms",
+        *rendering,
+        objects,
+        samples.len(),
+        mean,
+        1_000.0 / mean,
+        percentile(0.5),
+        percentile(0.95)
+    );
+    exit.write(AppExit::Success);
+}
+
+/// Headless propagation benchmark: propagate `count` synthetic objects over
+/// a series of time steps, once serially and once on the compute task pool,
+/// and print the throughput of each. No window or renderer is started.
+pub fn run_propagation_benchmark(count: usize) {
+    let pool = ComputeTaskPool::get_or_init(TaskPool::default);
+    let epoch = Utc::now();
+    let mut sats: Vec<DebrisSat> = synthetic_catalog(count, epoch)
+        .entries
+        .into_iter()
+        .map(|entry| DebrisSat {
+            name: entry.name,
+            elements: entry.elements,
+            satrec: entry.satrec,
+        })
+        .collect();
+    let start_jd = julian_date(epoch);
+
+    let mut time_pass =
+        |label: &str, propagate: fn(&mut [DebrisSat], f64, f64) -> Vec<Propagated>| {
+            let started = Instant::now();
+            let mut failures = 0;
+            for step in 0..PROPAGATION_STEPS {
+                // One minute apart, starting at the epoch.
+                let jd_full = start_jd + step as f64 / 1_440.0;
+                let jd = jd_full.floor();
+                failures += propagate(&mut sats, jd, jd_full - jd)
+                    .iter()
+                    .filter(|p| p.is_err())
+                    .count();
+            }
+            let secs = started.elapsed().as_secs_f64();
+            let rate = (count * PROPAGATION_STEPS) as f64 / secs;
+            println!(
+                "{:>8}: {} objects x {} steps in {:.3} s ({:.2} ms/step, {:.0} propagations/s, {} failures)",
+                label,
+                count,
+                PROPAGATION_STEPS,
+                secs,
+                secs * 1_000.0 / PROPAGATION_STEPS as f64,
+                rate,
+                failures
+            );
+            rate
+        };
+
+    let serial = time_pass("serial", propagate_serial);
+    let parallel = time_pass("parallel", propagate_parallel);
+    println!(
+        "speedup: {:.2}x on {} threads",
+        parallel / serial,
+        pool.thread_num()
+    );
+}
@@ -5,11 +5,30 @@ use bevy::{
     prelude::*,
 };
 
+use crate::debris::Debris;
+use crate::earth::{DisplayFrame, SceneFrame};
+use crate::selection::Selection;
+
+/// Length of the eased move onto (or away from) a followed object, seconds.
+const FOCUS_SECS: f32 = 1.0;
+/// Distance the camera settles at when it starts following an object, world
+/// units (about 300 km).
+const FOLLOW_RADIUS: f32 = 0.05;
+/// Near clip plane, world units: close enough to keep a followed object in
+/// view at the smallest follow radius.
+const NEAR_PLANE: f32 = 0.001;
+/// Closest the camera comes to the Earth's centre while following an object,
+/// world units (Earth radii): about 30 km above the surface.
+const EARTH_CLEARANCE: f32 = 1.005;
+
 /// Global settings for the orbit camera (speed + limits)
 #[derive(Debug, Resource)]
 pub struct CameraSettings {
-    /// Min / max distance from target (Earth center)
+    /// Min / max distance from the target while it is the Earth (or a fixed
+    /// point from --camera-target)
     pub radius_range: Range<f32>,
+    /// Min / max distance from a followed object
+    pub follow_radius_range: Range<f32>,
     /// Allowed pitch range so we never flip over the poles
     pub pitch_range: Range<f32>,
     /// How fast mouse movement rotates the camera
@@ -23,6 +42,7 @@ impl Default for CameraSettings {
         let pitch_limit = FRAC_PI_2 - 0.01; // just shy of ±90°
         Self {
             radius_range: 1.5..50.0,
+            follow_radius_range: 0.002..50.0,
             pitch_range: -pitch_limit..pitch_limit,
             rotate_speed: 0.005,
             zoom_speed: 0.15,
@@ -30,6 +50,17 @@ impl Default for CameraSettings {
     }
 }
 
+impl CameraSettings {
+    /// Distance limits around the camera's current target.
+    fn radius_limits(&self, orbit: &OrbitCamera) -> Range<f32> {
+        if orbit.focus.is_some() {
+            self.follow_radius_range.clone()
+        } else {
+            self.radius_range.clone()
+        }
+    }
+}
+
 /// State for an orbit camera:
 /// yaw   -> rotation around Y (longitude)
 /// pitch -> tilt north/south (latitude)
@@ -40,6 +71,10 @@ pub struct OrbitCamera {
     pub pitch: f32,
     pub radius: f32,
     pub target: Vec3,
+    /// Debris object the camera follows; `None` looks at `target`.
+    pub focus: Option<Entity>,
+    /// Eased move of the target and radius after a change of focus.
+    transition: Option<FocusTransition>,
 }
 
 impl Default for OrbitCamera {
@@ -50,11 +85,54 @@ impl Default for OrbitCamera {
             pitch: 0.0,
             radius: 4.0,
             target: Vec3::ZERO,
+            focus: None,
+            transition: None,
         }
     }
 }
 
+/// Where a change of focus started and the radius it ends at. The end
+/// target is wherever the new focus is by then.
+#[derive(Debug, Clone, Copy)]
+struct FocusTransition {
+    from_target: Vec3,
+    from_radius: f32,
+    to_radius: f32,
+    /// 0 to 1.
+    progress: f32,
+}
+
 impl OrbitCamera {
+    /// Ease from the current view to `focus` (the Earth for `None`) at
+    /// `radius`.
+    fn refocus(&mut self, focus: Option<Entity>, radius: f32) {
+        self.focus = focus;
+        self.transition = Some(FocusTransition {
+            from_target: self.target,
+            from_radius: self.radius,
+            to_radius: radius,
+            progress: 0.0,
+        });
+    }
+
+    /// Rotate the camera (position and target) about the origin.
+    fn rotate_about_origin(&mut self, rotation: Quat, settings: &CameraSettings) {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        let dir = rotation * Vec3::new(cos_yaw * cos_pitch, sin_pitch, sin_yaw * cos_pitch);
+
+        self.yaw = dir.z.atan2(dir.x);
+        self.pitch = dir
+            .y
+            .clamp(-1.0, 1.0)
+            .asin()
+            .clamp(settings.pitch_range.start, settings.pitch_range.end);
+        self.target = rotation * self.target;
+        if let Some(transition) = &mut self.transition {
+            transition.from_target = rotation * transition.from_target;
+        }
+    }
+
     /// Convert yaw/pitch/radius into a Transform
     fn update_transform(&self, transform: &mut Transform) {
         let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
@@ -67,14 +145,48 @@ impl OrbitCamera {
             sin_yaw * cos_pitch, // Z
         );
 
-        transform.translation = self.target + dir * self.radius;
+        let mut position = self.target + dir * self.radius;
+        // Orbiting a low object can swing the camera to its Earth-facing
+        // side; lift it back above the surface instead of into the globe.
+        if self.focus.is_some() && position.length() < EARTH_CLEARANCE {
+            position = position.try_normalize().unwrap_or(dir) * EARTH_CLEARANCE;
+        }
+        transform.translation = position;
         transform.look_at(self.target, Vec3::Y);
     }
 }
 
-/// Spawn the 3D camera as an orbit camera around the origin (Earth)
-pub fn setup_camera(mut commands: Commands) {
-    let orbit = OrbitCamera::default();
+/// Initial camera placement, from the command line.
+#[derive(Debug, Resource)]
+pub struct CameraStart {
+    pub target: Vec3,
+    pub radius: f32,
+}
+
+impl Default for CameraStart {
+    fn default() -> Self {
+        let orbit = OrbitCamera::default();
+        Self {
+            target: orbit.target,
+            radius: orbit.radius,
+        }
+    }
+}
+
+/// Spawn the 3D camera as an orbit camera around the start target (Earth by
+/// default)
+pub fn setup_camera(
+    mut commands: Commands,
+    settings: Res<CameraSettings>,
+    start: Res<CameraStart>,
+) {
+    let orbit = OrbitCamera {
+        target: start.target,
+        radius: start
+            .radius
+            .clamp(settings.radius_range.start, settings.radius_range.end),
+        ..default()
+    };
 
     let mut transform = Transform::default();
     orbit.update_transform(&mut transform);
@@ -82,6 +194,10 @@ pub fn setup_camera(mut commands: Commands) {
     commands.spawn((
         Name::new("Camera"),
         Camera3d::default(),
+        Projection::Perspective(PerspectiveProjection {
+            near: NEAR_PLANE,
+            ..default()
+        }),
         transform,
         GlobalTransform::default(),
         orbit,
@@ -94,12 +210,18 @@ pub fn orbit_camera(
     settings: Res<CameraSettings>,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     mouse_motion: Res<AccumulatedMouseMotion>,
+    ui_interactions: Query<&Interaction>,
 ) {
     // Only rotate while holding left mouse button
     if !mouse_buttons.pressed(MouseButton::Left) {
         return;
     }
 
+    // The drag belongs to a UI widget (e.g. the timeline), not the camera.
+    if ui_interactions.iter().any(|i| *i == Interaction::Pressed) {
+        return;
+    }
+
     let (mut transform, mut orbit) = query.into_inner();
 
     let delta = mouse_motion.delta;
@@ -136,8 +258,143 @@ pub fn zoom_camera(
     // Smooth zoom: scale radius based on scroll.
     // Positive scroll_y (wheel up) should zoom in => shrink radius.
     let zoom_factor = (1.0 - scroll_y * settings.zoom_speed).max(0.1);
-    orbit.radius =
-        (orbit.radius * zoom_factor).clamp(settings.radius_range.start, settings.radius_range.end);
+    let limits = settings.radius_limits(&orbit);
+    orbit.radius = (orbit.radius * zoom_factor).clamp(limits.start, limits.end);
+    if let Some(transition) = &mut orbit.transition {
+        // Zooming mid-transition: land at the zoomed distance.
+        transition.to_radius = (transition.to_radius * zoom_factor).clamp(limits.start, limits.end);
+    }
 
     orbit.update_transform(&mut transform);
 }
+
+/// When the display frame changes, carry the camera along with the Earth so
+/// the view doesn't jump: it keeps looking at the same spot on the ground.
+pub fn follow_display_frame(
+    query: Single<(&mut Transform, &mut OrbitCamera), With<Camera>>,
+    settings: Res<CameraSettings>,
+    scene: SceneFrame,
+    mut earth_orientation: Local<Option<(DisplayFrame, Quat)>>,
+) {
+    let (mut transform, mut orbit) = query.into_inner();
+    let frame = *scene.frame;
+    let orientation = scene.itrf_to_display();
+
+    if let Some((previous_frame, previous)) = *earth_orientation
+        && previous_frame != frame
+    {
+        orbit.rotate_about_origin(orientation * previous.inverse(), &settings);
+        orbit.update_transform(&mut transform);
+    }
+    *earth_orientation = Some((frame, orientation));
+}
+
+/// T follows the selected object; Shift+T goes back to the start target
+/// (the Earth, unless --camera-target moved it) and distance.
+pub fn focus_camera(
+    keys: Res<ButtonInput<KeyCode>>,
+    selection: Res<Selection>,
+    settings: Res<CameraSettings>,
+    start: Res<CameraStart>,
+    mut orbit: Single<&mut OrbitCamera>,
+) {
+    if !keys.just_pressed(KeyCode::KeyT) {
+        return;
+    }
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        let radius = start
+            .radius
+            .clamp(settings.radius_range.start, settings.radius_range.end);
+        orbit.refocus(None, radius);
+    } else if let Some(entity) = selection.selected
+        && orbit.focus != Some(entity)
+    {
+        // Already close to something: keep the distance.
+        let radius = if orbit.focus.is_some() {
+            orbit.radius
+        } else {
+            FOLLOW_RADIUS
+        };
+        orbit.refocus(Some(entity), radius);
+    }
+}
+
+/// Keep the camera on its followed object as it moves, easing in over
+/// `FOCUS_SECS` after a change of focus. Orbiting and zooming still work
+/// around the object. Falls back to the Earth if the object goes away (e.g.
+/// on a catalog reload).
+pub fn follow_focus(
+    time: Res<Time>,
+    settings: Res<CameraSettings>,
+    start: Res<CameraStart>,
+    debris: Query<&Transform, (With<Debris>, Without<OrbitCamera>)>,
+    query: Single<(&mut Transform, &mut OrbitCamera), With<Camera>>,
+) {
+    let (mut transform, mut orbit) = query.into_inner();
+    let destination = match orbit.focus.map(|entity| debris.get(entity)) {
+        Some(Ok(followed)) => followed.translation,
+        Some(Err(_)) => {
+            let radius = start
+                .radius
+                .clamp(settings.radius_range.start, settings.radius_range.end);
+            orbit.refocus(None, radius);
+            start.target
+        }
+        None => start.target,
+    };
+
+    match orbit.transition {
+        Some(mut transition) => {
+            transition.progress = (transition.progress + time.delta_secs() / FOCUS_SECS).min(1.0);
+            let t = transition.progress;
+            let eased = t * t * (3.0 - 2.0 * t);
+            orbit.target = transition.from_target.lerp(destination, eased);
+            // Interpolate the distance geometrically so zooming from Earth
+            // scale down to an object doesn't rush the last part.
+            orbit.radius = (transition.from_radius.ln()
+                + (transition.to_radius.ln() - transition.from_radius.ln()) * eased)
+                .exp();
+            orbit.transition = (t < 1.0).then_some(transition);
+        }
+        None if orbit.focus.is_some() => orbit.target = destination,
+        None => return,
+    }
+    orbit.update_transform(&mut transform);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    #[test]
+    fn following_keeps_the_camera_out_of_the_earth() {
+        // An object 300 km up on +X, seen from below at 0.3 Earth radii.
+        let mut orbit = OrbitCamera {
+            yaw: PI,
+            radius: 0.3,
+            target: Vec3::new(1.05, 0.0, 0.0),
+            ..default()
+        };
+        let mut transform = Transform::default();
+        orbit.update_transform(&mut transform);
+        assert!(transform.translation.length() < 1.0, "{transform:?}");
+
+        orbit.focus = Some(Entity::PLACEHOLDER);
+        orbit.update_transform(&mut transform);
+        let distance = transform.translation.length();
+        assert!(
+            (distance - EARTH_CLEARANCE).abs() < 1e-5,
+            "camera {distance} from the centre"
+        );
+        // Still looking at the object.
+        let to_target = (orbit.target - transform.translation).normalize();
+        assert!(transform.forward().dot(to_target) > 0.9999);
+
+        // Above the object it is left alone.
+        orbit.yaw = 0.0;
+        orbit.update_transform(&mut transform);
+        assert!((transform.translation.x - 1.35).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,454 @@
+use std::path::{Path, PathBuf};
+
+use bevy::asset::io::AssetSourceBuilder;
+use bevy::prelude::*;
+use bevy::window::PresentMode;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use clap::Parser;
+
+use crate::benchmark::FrameBenchmark;
+use crate::camera::CameraStart;
+use crate::collision::{CollisionModel, CovarianceTable, DEFAULT_HARD_BODY_M};
+use crate::coloring::ColorScheme;
+use crate::conjunction::ScreeningSettings;
+use crate::debris::{CatalogSources, SimulationConfig};
+use crate::earth::{DisplayFrame, EarthRotation, NightLightsSource};
+use crate::filter::Filter;
+use crate::filtering::ActiveFilter;
+use crate::frames::PolarMotion;
+use crate::ground_track::GroundTrackSettings;
+use crate::instancing::DebrisRendering;
+use crate::orbits::OrbitTrailSettings;
+use crate::propagation::PropagationSettings;
+use crate::satcat::SatcatSource;
+use crate::sensors::{Sensor, Sensors};
+use crate::stations::{GroundStation, GroundStations};
+use crate::synthetic::SyntheticPopulation;
+use crate::timeline::TimelineSettings;
+use crate::visibility::OpticalSettings;
+
+/// Space debris visualization driven by SGP4.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Catalog file(s) to load: TLE/3LE, CCSDS OMM (XML or KVN), or CelesTrak
+    /// GP JSON/CSV. Defaults to the bundled sample catalog.
+    #[arg(value_name = "CATALOG", value_parser = existing_file)]
+    pub catalogs: Vec<PathBuf>,
+
+    /// CelesTrak SATCAT CSV to join onto the catalog by NORAD number, for
+    /// owner, launch, object type and RCS details.
+    #[arg(long, value_name = "CSV", value_parser = existing_file)]
+    pub satcat: Option<PathBuf>,
+
+    /// Only show objects matching this query, e.g.
+    /// `inc > 95 and alt_perigee < 600 and type = DEBRIS` (edit with /).
+    #[arg(long, value_name = "QUERY", value_parser = Filter::parse)]
+    pub filter: Option<Filter>,
+
+    /// Print the objects matching --filter (all, without one) as
+    /// tab-separated text and exit, without opening a window.
+    #[arg(long)]
+    pub list: bool,
+
+    /// Screen the catalog for conjunctions from --epoch (or now), print them
+    /// as CSV and exit, without opening a window.
+    #[arg(long)]
+    pub screen: bool,
+
+    /// Hours covered by conjunction screening (K in the app, or --screen).
+    #[arg(long, value_name = "HOURS", value_parser = parse_positive, default_value_t = 24.0)]
+    pub screen_hours: f64,
+
+    /// Miss distance below which screening reports a conjunction, km.
+    #[arg(long, value_name = "KM", value_parser = parse_positive, default_value_t = 5.0)]
+    pub screen_km: f64,
+
+    /// Hard-body radius of each object for collision probabilities, m;
+    /// --covariance can override it per object.
+    #[arg(long, value_name = "M", default_value_t = DEFAULT_HARD_BODY_M)]
+    pub hard_body_m: f64,
+
+    /// CSV of per-object position uncertainties (NORAD_CAT_ID, SIGMA_R_KM,
+    /// SIGMA_I_KM, SIGMA_C_KM, 1-sigma radial / in-track / cross-track) and
+    /// hard-body radii (HARD_BODY_M). Objects it doesn't list use a TLE error
+    /// model that grows with element age.
+    #[arg(long, value_name = "CSV", value_parser = CovarianceTable::read)]
+    pub covariance: Option<CovarianceTable>,
+
+    /// Ground station as NAME,LAT,LON[,ALT_M[,MIN_EL]] (degrees, metres above
+    /// the ellipsoid, elevation mask in degrees, default 10). Repeatable.
+    #[arg(
+        long = "station",
+        value_name = "STATION",
+        value_parser = GroundStation::parse,
+        allow_hyphen_values = true
+    )]
+    pub stations: Vec<GroundStation>,
+
+    /// Print the passes of the objects matching --filter (all, without one)
+    /// over each --station from --epoch (or now) as CSV and exit, without
+    /// opening a window.
+    #[arg(long)]
+    pub passes: bool,
+
+    /// Hours covered by --passes.
+    #[arg(long, value_name = "HOURS", value_parser = parse_positive, default_value_t = 24.0)]
+    pub pass_hours: f64,
+
+    /// With --passes, print azimuth / elevation / range every 30 s of each
+    /// pass instead of one row per pass.
+    #[arg(long)]
+    pub pass_track: bool,
+
+    /// Print the passes of the objects matching --filter that can be seen
+    /// optically from each --station in its next night after --epoch (or
+    /// now) as CSV and exit, without opening a window.
+    #[arg(long)]
+    pub tonight: bool,
+
+    /// Highest Sun elevation, degrees, at which a site is dark enough to see
+    /// satellites: -6 is civil twilight, -12 nautical.
+    #[arg(long, value_name = "DEG", default_value_t = -6.0, allow_hyphen_values = true)]
+    pub twilight: f64,
+
+    /// Faintest visual magnitude counted as visible: about 6 by eye under
+    /// dark skies, 10 or more with a telescope.
+    #[arg(
+        long,
+        value_name = "MAG",
+        default_value_t = 6.0,
+        allow_hyphen_values = true
+    )]
+    pub limiting_magnitude: f64,
+
+    /// Albedo assumed for every object when estimating its brightness.
+    #[arg(long, default_value_t = 0.2)]
+    pub albedo: f64,
+
+    /// Sensor as NAME,LAT,LON,ALT_M,AZ,EL,FOV,RANGE_KM: site in degrees and
+    /// metres, boresight azimuth / elevation in degrees, FOV as cone:ANGLE or
+    /// fan:WIDTHxHEIGHT (full angles, degrees) and range limit in km.
+    /// Repeatable.
+    #[arg(
+        long = "sensor",
+        value_name = "SENSOR",
+        value_parser = Sensor::parse,
+        allow_hyphen_values = true
+    )]
+    pub sensors: Vec<Sensor>,
+
+    /// Print how many unique objects matching --filter each --sensor would
+    /// detect from --epoch (or now) as CSV and exit, without opening a
+    /// window.
+    #[arg(long)]
+    pub coverage: bool,
+
+    /// Hours covered by --coverage.
+    #[arg(long, value_name = "HOURS", value_parser = parse_positive, default_value_t = 24.0)]
+    pub coverage_hours: f64,
+
+    /// Sampling step of --coverage, seconds. Objects crossing a volume
+    /// between samples are missed, so thin fans need short steps.
+    #[arg(long, value_name = "SECS", value_parser = parse_positive, default_value_t = 10.0)]
+    pub coverage_step: f64,
+
+    /// Simulation start time as ISO-8601 UTC, e.g. 2025-12-04T13:00:00Z.
+    /// Defaults to the current time.
+    #[arg(long, value_parser = parse_epoch)]
+    pub epoch: Option<DateTime<Utc>>,
+
+    /// Simulated seconds per real second. Negative runs the clock backwards.
+    #[arg(long, default_value_t = 1.0, allow_negative_numbers = true)]
+    pub time_scale: f64,
+
+    /// Days either side of the start epoch covered by the timeline bar.
+    #[arg(long, value_parser = parse_positive, default_value_t = 3.0)]
+    pub timeline_days: f64,
+
+    /// Orbital periods covered by the orbit trail of selected/pinned objects.
+    #[arg(long, value_parser = parse_positive, default_value_t = 1.0)]
+    pub orbit_revolutions: f64,
+
+    /// Minutes of ground track drawn before and after the current sim time
+    /// (toggle with G).
+    #[arg(long, value_parser = parse_positive, default_value_t = 90.0)]
+    pub ground_track_minutes: f64,
+
+    /// Frame to draw the scene in (toggle with F).
+    #[arg(long, value_enum, default_value_t = DisplayFrame::Inertial)]
+    pub frame: DisplayFrame,
+
+    /// Polar motion `xp,yp` in arcseconds (IERS Bulletin A), applied when
+    /// converting to Earth-fixed coordinates.
+    #[arg(long, value_name = "XP,YP", value_parser = parse_polar_motion, allow_hyphen_values = true)]
+    pub polar_motion: Option<PolarMotion>,
+
+    /// Initial camera target as `x,y,z` in Earth radii.
+    #[arg(long, value_name = "X,Y,Z", value_parser = parse_vec3, allow_hyphen_values = true)]
+    pub camera_target: Option<Vec3>,
+
+    /// Initial camera distance from the target, in Earth radii.
+    #[arg(long, default_value_t = 4.0)]
+    pub camera_radius: f32,
+
+    /// Window size as `WIDTHxHEIGHT`.
+    #[arg(long, value_name = "WIDTHxHEIGHT", value_parser = parse_window_size, default_value = "1280x720")]
+    pub window_size: UVec2,
+
+    /// How to draw debris objects.
+    #[arg(long, value_enum, default_value_t = DebrisRendering::Instanced)]
+    pub render: DebrisRendering,
+
+    /// Equirectangular image of city lights to show on the Earth's night
+    /// side, aligned like the day texture (e.g. NASA's Black Marble).
+    #[arg(long, value_name = "IMAGE", value_parser = existing_file)]
+    pub night_lights: Option<PathBuf>,
+
+    /// What debris colors encode (cycle with C).
+    #[arg(long, value_enum, default_value_t = ColorScheme::Regime)]
+    pub color_by: ColorScheme,
+
+    /// Add this many random LEO objects, for load testing. With no CATALOG,
+    /// the bundled sample catalog is left out.
+    #[arg(long, value_name = "COUNT", default_value_t = 0)]
+    pub synthetic: usize,
+
+    /// Propagate the debris field this many times per second of real time
+    /// and interpolate in between, instead of every frame.
+    #[arg(long, value_name = "HZ")]
+    pub propagation_hz: Option<f64>,
+
+    /// Run the headless propagation benchmark (serial vs. parallel) on COUNT
+    /// synthetic objects and exit.
+    #[arg(long, value_name = "COUNT", num_args = 0..=1, default_missing_value = "30000")]
+    pub bench_propagation: Option<usize>,
+
+    /// Time this many frames (vsync off) once the debris field has loaded,
+    /// log the frame times and exit.
+    #[arg(long, value_name = "FRAMES")]
+    pub bench_frames: Option<usize>,
+}
+
+impl Cli {
+    /// Register an asset source for each catalog's directory and return the
+    /// asset paths to load. Going through a named source (rather than an
+    /// absolute path on the default one) keeps hot reload working for files
+    /// outside `assets/`. Must run before `DefaultPlugins` is added.
+    pub fn register_catalog_sources(&self, app: &mut App) -> CatalogSources {
+        if self.catalogs.is_empty() {
+            if self.synthetic > 0 {
+                return CatalogSources(Vec::new());
+            }
+            return CatalogSources::default();
+        }
+
+        let paths = self
+            .catalogs
+            .iter()
+            .enumerate()
+            .map(|(i, path)| register_file_source(app, &format!("catalog{}", i), path))
+            .collect();
+        CatalogSources(paths)
+    }
+
+    /// Register an asset source for the SATCAT's directory, like the
+    /// catalogs', and return its asset path.
+    pub fn register_satcat_source(&self, app: &mut App) -> SatcatSource {
+        SatcatSource(
+            self.satcat
+                .as_ref()
+                .map(|path| register_file_source(app, "satcat", path)),
+        )
+    }
+
+    /// Register an asset source for the night-lights texture, like the
+    /// SATCAT's.
+    pub fn register_night_lights_source(&self, app: &mut App) -> NightLightsSource {
+        NightLightsSource(
+            self.night_lights
+                .as_ref()
+                .map(|path| register_file_source(app, "night_lights", path)),
+        )
+    }
+
+    pub fn window(&self) -> Window {
+        let present_mode = if self.bench_frames.is_some() {
+            PresentMode::AutoNoVsync
+        } else {
+            PresentMode::default()
+        };
+        Window {
+            resolution: self.window_size.into(),
+            present_mode,
+            ..default()
+        }
+    }
+
+    pub fn screening_settings(&self) -> ScreeningSettings {
+        ScreeningSettings {
+            window_days: self.screen_hours / 24.0,
+            threshold_km: self.screen_km,
+            collision: CollisionModel {
+                hard_body_m: self.hard_body_m,
+                table: self.covariance.clone().unwrap_or_default(),
+            },
+            ..default()
+        }
+    }
+
+    pub fn ground_stations(&self) -> GroundStations {
+        GroundStations(self.stations.clone())
+    }
+
+    pub fn optical_settings(&self) -> OpticalSettings {
+        OpticalSettings {
+            twilight: self.twilight.to_radians(),
+            limiting_magnitude: self.limiting_magnitude,
+            albedo: self.albedo,
+        }
+    }
+
+    pub fn sensors(&self) -> Sensors {
+        Sensors(self.sensors.clone())
+    }
+
+    pub fn active_filter(&self) -> ActiveFilter {
+        ActiveFilter {
+            filter: self.filter.clone(),
+            ..default()
+        }
+    }
+
+    pub fn synthetic_population(&self) -> SyntheticPopulation {
+        SyntheticPopulation {
+            count: self.synthetic,
+        }
+    }
+
+    pub fn propagation_settings(&self) -> PropagationSettings {
+        PropagationSettings {
+            rate_hz: self.propagation_hz.filter(|hz| *hz > 0.0),
+        }
+    }
+
+    pub fn frame_benchmark(&self) -> Option<FrameBenchmark> {
+        self.bench_frames.map(FrameBenchmark::new)
+    }
+
+    pub fn simulation_config(&self) -> SimulationConfig {
+        SimulationConfig {
+            start_epoch: self.epoch,
+            time_scale: self.time_scale,
+        }
+    }
+
+    pub fn timeline_settings(&self) -> TimelineSettings {
+        TimelineSettings {
+            span_days: self.timeline_days,
+        }
+    }
+
+    pub fn orbit_trail_settings(&self) -> OrbitTrailSettings {
+        OrbitTrailSettings {
+            revolutions: self.orbit_revolutions,
+            ..default()
+        }
+    }
+
+    pub fn ground_track_settings(&self) -> GroundTrackSettings {
+        GroundTrackSettings {
+            minutes: self.ground_track_minutes,
+            ..default()
+        }
+    }
+
+    pub fn earth_rotation(&self) -> EarthRotation {
+        EarthRotation {
+            polar_motion: self.polar_motion.unwrap_or_default(),
+            ..default()
+        }
+    }
+
+    pub fn camera_start(&self) -> CameraStart {
+        CameraStart {
+            target: self.camera_target.unwrap_or(Vec3::ZERO),
+            radius: self.camera_radius,
+        }
+    }
+}
+
+/// Register asset source `source` for `path`'s directory and return the
+/// file's asset path on it.
+fn register_file_source(app: &mut App, source: &str, path: &Path) -> String {
+    let dir = path.parent().unwrap_or(path).to_string_lossy();
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    app.register_asset_source(
+        source.to_string(),
+        AssetSourceBuilder::platform_default(&dir, None),
+    );
+    format!("{}://{}", source, file_name)
+}
+
+/// Resolve to an absolute path so asset sources don't depend on Bevy's idea
+/// of the base directory.
+fn existing_file(arg: &str) -> Result<PathBuf, String> {
+    let path = std::fs::canonicalize(arg).map_err(|e| format!("{}: {}", arg, e))?;
+    if !path.is_file() {
+        return Err(format!("{}: not a file", arg));
+    }
+    Ok(path)
+}
+
+/// Accepts RFC 3339 (`2025-12-04T13:00:00Z`, `...+00:00`) or a bare
+/// `2025-12-04T13:00:00`, which is taken as UTC.
+fn parse_epoch(arg: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(epoch) = DateTime::parse_from_rfc3339(arg) {
+        return Ok(epoch.with_timezone(&Utc));
+    }
+    NaiveDateTime::parse_from_str(arg, "%Y-%m-%dT%H:%M:%S%.f")
+        .map(|naive| naive.and_utc())
+        .map_err(|e| format!("expected ISO-8601 UTC time: {}", e))
+}
+
+fn parse_vec3(arg: &str) -> Result<Vec3, String> {
+    let parts = arg
+        .split(',')
+        .map(|p| p.trim().parse::<f32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    match parts[..] {
+        [x, y, z] => Ok(Vec3::new(x, y, z)),
+        _ => Err("expected three comma-separated numbers".to_string()),
+    }
+}
+
+fn parse_polar_motion(arg: &str) -> Result<PolarMotion, String> {
+    let parts = arg
+        .split(',')
+        .map(|p| p.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    match parts[..] {
+        [xp, yp] => Ok(PolarMotion::from_arcsec(xp, yp)),
+        _ => Err("expected two comma-separated numbers".to_string()),
+    }
+}
+
+fn parse_positive(arg: &str) -> Result<f64, String> {
+    let value = arg.trim().parse::<f64>().map_err(|e| e.to_string())?;
+    if value > 0.0 && value.is_finite() {
+        Ok(value)
+    } else {
+        Err("must be a positive number".to_string())
+    }
+}
+
+fn parse_window_size(arg: &str) -> Result<UVec2, String> {
+    let (width, height) = arg
+        .split_once(['x', 'X'])
+        .ok_or_else(|| "expected WIDTHxHEIGHT".to_string())?;
+    let width = width.trim().parse::<u32>().map_err(|e| e.to_string())?;
+    let height = height.trim().parse::<u32>().map_err(|e| e.to_string())?;
+    Ok(UVec2::new(width, height))
+}
@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::debris::{Debris, DebrisField, DebrisSat, DebrisStyle, SimulationTime};
+use crate::elements::MeanElements;
+use crate::satcat::{ObjectType, SatelliteMetadata};
+use crate::sun::Illumination;
+
+/// Steps the gradient schemes are quantized to, so the per-entity renderer
+/// only ever needs a handful of materials.
+const GRADIENT_STEPS: f32 = 32.0;
+/// Swatches shown in the legend for gradient schemes.
+const LEGEND_STOPS: usize = 5;
+/// Sim time (days) after which TLE ages are recomputed.
+const AGE_REFRESH_DAYS: f64 = 1.0 / 24.0;
+
+/// What debris colors encode. C cycles through them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource, clap::ValueEnum)]
+pub enum ColorScheme {
+    /// LEO / MEO / GEO / HEO / GTO.
+    #[default]
+    Regime,
+    /// Payload, rocket body, debris or unknown.
+    ObjectType,
+    /// Mean altitude, log scale.
+    Altitude,
+    Inclination,
+    /// Time between the TLE epoch and the sim time.
+    TleAge,
+    /// Sunlit, in penumbra or in the Earth's umbra.
+    Illumination,
+}
+
+impl ColorScheme {
+    const ALL: [ColorScheme; 6] = [
+        ColorScheme::Regime,
+        ColorScheme::ObjectType,
+        ColorScheme::Altitude,
+        ColorScheme::Inclination,
+        ColorScheme::TleAge,
+        ColorScheme::Illumination,
+    ];
+
+    fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|s| *s == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            ColorScheme::Regime => "Orbit regime",
+            ColorScheme::ObjectType => "Object type",
+            ColorScheme::Altitude => "Mean altitude",
+            ColorScheme::Inclination => "Inclination",
+            ColorScheme::TleAge => "TLE age",
+            ColorScheme::Illumination => "Illumination",
+        }
+    }
+
+    /// Color of one object; `age_days` is its TLE age at the sim time. The
+    /// object type comes from the SATCAT when there is one, else the name.
+    fn color(
+        self,
+        sat: &DebrisSat,
+        metadata: Option<&SatelliteMetadata>,
+        illumination: Illumination,
+        age_days: f64,
+    ) -> Color {
+        match self {
+            ColorScheme::Regime => OrbitRegime::of(&sat.elements).color(),
+            ColorScheme::ObjectType => object_type_color(metadata.map_or_else(
+                || ObjectType::from_name(sat.name.as_deref()),
+                |m| m.object_type,
+            )),
+            ColorScheme::Altitude => ALTITUDE.color(mean_altitude_km(&sat.elements)),
+            ColorScheme::Inclination => INCLINATION.color(sat.elements.inclination),
+            ColorScheme::TleAge => TLE_AGE.color(age_days),
+            ColorScheme::Illumination => illumination_color(illumination),
+        }
+    }
+
+    /// Swatches and labels for the legend.
+    fn legend(self) -> Vec<(Color, String)> {
+        match self {
+            ColorScheme::Regime => OrbitRegime::ALL
+                .iter()
+                .map(|r| (r.color(), r.label().to_string()))
+                .collect(),
+            ColorScheme::ObjectType => ObjectType::ALL
+                .iter()
+                .map(|t| (object_type_color(*t), t.label().to_string()))
+                .collect(),
+            ColorScheme::Altitude => ALTITUDE.legend(),
+            ColorScheme::Inclination => INCLINATION.legend(),
+            ColorScheme::TleAge => TLE_AGE.legend(),
+            ColorScheme::Illumination => Illumination::ALL
+                .iter()
+                .map(|i| (illumination_color(*i), i.label().to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Orbit regime, from the mean elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrbitRegime {
+    /// Apogee below 2,000 km.
+    Leo,
+    /// Between LEO and GEO, not very eccentric.
+    Meo,
+    /// About one revolution per sidereal day, near-circular.
+    Geo,
+    /// Highly eccentric (Molniya, Tundra, ...) or beyond GEO.
+    Heo,
+    /// Low perigee, apogee near GEO altitude.
+    Gto,
+}
+
+impl OrbitRegime {
+    const ALL: [OrbitRegime; 5] = [
+        OrbitRegime::Leo,
+        OrbitRegime::Meo,
+        OrbitRegime::Geo,
+        OrbitRegime::Heo,
+        OrbitRegime::Gto,
+    ];
+
+    pub fn of(elements: &MeanElements) -> Self {
+        let perigee = elements.perigee_altitude_km();
+        let apogee = elements.apogee_altitude_km();
+        if (0.9..1.1).contains(&elements.mean_motion) && elements.eccentricity < 0.1 {
+            OrbitRegime::Geo
+        } else if perigee < 2_000.0 && (30_000.0..40_000.0).contains(&apogee) {
+            OrbitRegime::Gto
+        } else if apogee < 2_000.0 {
+            OrbitRegime::Leo
+        } else if elements.eccentricity < 0.25 && apogee < 30_000.0 {
+            OrbitRegime::Meo
+        } else {
+            OrbitRegime::Heo
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OrbitRegime::Leo => "LEO",
+            OrbitRegime::Meo => "MEO",
+            OrbitRegime::Geo => "GEO",
+            OrbitRegime::Heo => "HEO",
+            OrbitRegime::Gto => "GTO",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            OrbitRegime::Leo => Color::srgb(0.3, 0.8, 1.0),
+            OrbitRegime::Meo => Color::srgb(0.4, 1.0, 0.4),
+            OrbitRegime::Geo => Color::srgb(1.0, 0.85, 0.2),
+            OrbitRegime::Heo => Color::srgb(0.9, 0.4, 1.0),
+            OrbitRegime::Gto => Color::srgb(1.0, 0.5, 0.2),
+        }
+    }
+}
+
+fn object_type_color(object_type: ObjectType) -> Color {
+    match object_type {
+        ObjectType::Payload => Color::srgb(0.3, 1.0, 0.5),
+        ObjectType::RocketBody => Color::srgb(1.0, 0.6, 0.2),
+        ObjectType::Debris => Color::srgb(0.9, 0.2, 0.2),
+        ObjectType::Unknown => Color::srgb(0.6, 0.6, 0.6),
+    }
+}
+
+fn illumination_color(illumination: Illumination) -> Color {
+    match illumination {
+        Illumination::Sunlit => Color::srgb(1.0, 0.9, 0.4),
+        Illumination::Penumbra => Color::srgb(0.9, 0.45, 0.2),
+        Illumination::Umbra => Color::srgb(0.3, 0.35, 0.7),
+    }
+}
+
+/// A value range mapped onto a blue-to-red ramp.
+struct Gradient {
+    min: f64,
+    max: f64,
+    /// Interpolate in log space (for ranges spanning decades).
+    log: bool,
+    unit: &'static str,
+}
+
+const ALTITUDE: Gradient = Gradient {
+    min: 200.0,
+    max: 40_000.0,
+    log: true,
+    unit: " km",
+};
+
+const INCLINATION: Gradient = Gradient {
+    min: 0.0,
+    max: 180.0,
+    log: false,
+    unit: "°",
+};
+
+const TLE_AGE: Gradient = Gradient {
+    min: 0.0,
+    max: 30.0,
+    log: false,
+    unit: " d",
+};
+
+impl Gradient {
+    fn position(&self, value: f64) -> f32 {
+        let t = if self.log {
+            (value.max(self.min).ln() - self.min.ln()) / (self.max.ln() - self.min.ln())
+        } else {
+            (value - self.min) / (self.max - self.min)
+        };
+        t.clamp(0.0, 1.0) as f32
+    }
+
+    fn value_at(&self, t: f64) -> f64 {
+        if self.log {
+            (self.min.ln() + t * (self.max.ln() - self.min.ln())).exp()
+        } else {
+            self.min + t * (self.max - self.min)
+        }
+    }
+
+    fn color(&self, value: f64) -> Color {
+        ramp(self.position(value))
+    }
+
+    fn legend(&self) -> Vec<(Color, String)> {
+        (0..LEGEND_STOPS)
+            .map(|i| {
+                let t = i as f64 / (LEGEND_STOPS - 1) as f64;
+                let value = self.value_at(t);
+                let prefix = if i == LEGEND_STOPS - 1 { "≥ " } else { "" };
+                (
+                    ramp(t as f32),
+                    format!("{}{:.0}{}", prefix, value, self.unit),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Blue (0) through green to red (1), in `GRADIENT_STEPS` steps.
+fn ramp(t: f32) -> Color {
+    let t = (t * GRADIENT_STEPS).round() / GRADIENT_STEPS;
+    Color::hsl(240.0 * (1.0 - t), 0.9, 0.55)
+}
+
+fn mean_altitude_km(elements: &MeanElements) -> f64 {
+    (elements.perigee_altitude_km() + elements.apogee_altitude_km()) / 2.0
+}
+
+/// Objects whose color may have changed on their own.
+type NeedsRecolor = Or<(
+    Added<Debris>,
+    Changed<SatelliteMetadata>,
+    Changed<Illumination>,
+)>;
+
+/// Marker for the legend overlay.
+#[derive(Component)]
+pub struct ColorLegend;
+
+/// C cycles the color scheme.
+pub fn cycle_color_scheme(keys: Res<ButtonInput<KeyCode>>, mut scheme: ResMut<ColorScheme>) {
+    if keys.just_pressed(KeyCode::KeyC) {
+        *scheme = scheme.next();
+    }
+}
+
+/// Recolor every object when the scheme changes, objects are spawned, their
+/// SATCAT metadata changes or they cross a shadow boundary, and every
+/// `AGE_REFRESH_DAYS` of sim time while coloring by TLE age. Only
+/// `DebrisStyle` changes; entities are never respawned.
+pub fn apply_color_scheme(
+    scheme: Res<ColorScheme>,
+    sim_time: Res<SimulationTime>,
+    debris_field: Res<DebrisField>,
+    changed: Query<(), NeedsRecolor>,
+    mut removed: RemovedComponents<SatelliteMetadata>,
+    mut debris: Query<(
+        &Debris,
+        Option<&SatelliteMetadata>,
+        Option<&Illumination>,
+        &mut DebrisStyle,
+    )>,
+    mut aged_at: Local<f64>,
+) {
+    let now = sim_time.jd + sim_time.fr;
+    let ages_stale = *scheme == ColorScheme::TleAge && (now - *aged_at).abs() > AGE_REFRESH_DAYS;
+    let metadata_removed = removed.read().count() > 0;
+    if !scheme.is_changed() && changed.is_empty() && !metadata_removed && !ages_stale {
+        return;
+    }
+    *aged_at = now;
+
+    let utc = sim_time.utc();
+    for (debris, metadata, illumination, mut style) in &mut debris {
+        let Some(sat) = debris_field.sats.get(debris.sat_index) else {
+            continue;
+        };
+        let age_days = (utc - sat.elements.epoch).num_seconds().abs() as f64 / 86_400.0;
+        let illumination = illumination.copied().unwrap_or_default();
+        let color = scheme.color(sat, metadata, illumination, age_days);
+        if style.color != color {
+            style.color = color;
+        }
+    }
+}
+
+/// Per-entity rendering: point each recolored entity at a material of its
+/// color, creating one per distinct color as needed.
+pub fn update_debris_materials(
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut debris: Query<(&DebrisStyle, &mut MeshMaterial3d<StandardMaterial>), Changed<DebrisStyle>>,
+    mut cache: Local<HashMap<[u8; 4], Handle<StandardMaterial>>>,
+) {
+    for (style, mut material) in &mut debris {
+        let key = style.color.to_srgba().to_u8_array();
+        let handle = cache.entry(key).or_insert_with(|| {
+            materials.add(StandardMaterial {
+                base_color: style.color,
+                unlit: true,
+                ..default()
+            })
+        });
+        if material.0 != *handle {
+            material.0 = handle.clone();
+        }
+    }
+}
+
+/// Spawn the (empty) legend in the bottom-right corner.
+pub fn setup_color_legend(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Color Legend"),
+        ColorLegend,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(64.0),
+            right: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Interaction::default(),
+    ));
+}
+
+/// Rebuild the legend's entries for the active scheme.
+pub fn update_color_legend(
+    mut commands: Commands,
+    scheme: Res<ColorScheme>,
+    legend: Single<Entity, With<ColorLegend>>,
+) {
+    if !scheme.is_changed() {
+        return;
+    }
+    let font = TextFont {
+        font_size: 14.0,
+        ..default()
+    };
+
+    commands
+        .entity(*legend)
+        .despawn_related::<Children>()
+        .with_children(|legend| {
+            legend.spawn((
+                Text::new(format!("{} (C to change)", scheme.title())),
+                font.clone(),
+                TextColor(Color::WHITE),
+            ));
+            for (color, label) in scheme.legend() {
+                legend
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(6.0),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            Node {
+                                width: Val::Px(12.0),
+                                height: Val::Px(12.0),
+                                ..default()
+                            },
+                            BackgroundColor(color),
+                        ));
+                        row.spawn((Text::new(label), font.clone(), TextColor(Color::WHITE)));
+                    });
+            }
+        });
+}
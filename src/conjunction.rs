@@ -0,0 +1,666 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+use bevy::{
+    math::{DMat3, DVec3},
+    prelude::*,
+    tasks::{
+        AsyncComputeTaskPool, ComputeTaskPool, ParallelSlice, Task, TaskPool, futures::check_ready,
+    },
+};
+use chrono::{DateTime, Utc};
+
+use crate::collision::{CollisionModel, collision_probability};
+use crate::debris::{
+    Debris, DebrisField, DebrisSat, SimulationTime, datetime_from_jd, julian_date,
+};
+use crate::loader::read_catalogs;
+use crate::propagation::{StateVector, golden_section_min, propagate, propagate_parallel};
+use crate::selection::Selection;
+
+/// Closing speed assumed when sizing the coarse sieve. Head-on LEO
+/// encounters are a bit under 16 km/s.
+const MAX_RELATIVE_SPEED_KM_S: f64 = 16.0;
+/// Slack on the apogee/perigee filter: SGP4's osculating radius wanders
+/// around the mean-element perigee and apogee.
+const SHELL_MARGIN_KM: f64 = 25.0;
+/// TCA tolerance of the fine search, seconds.
+const TCA_TOLERANCE_SECS: f64 = 1e-3;
+/// Rows shown in the conjunction panel.
+const PANEL_ROWS: usize = 10;
+
+/// Time window and threshold for conjunction screening.
+#[derive(Debug, Clone, Resource)]
+pub struct ScreeningSettings {
+    /// Days screened, starting at the sim time.
+    pub window_days: f64,
+    /// Report pairs closer than this, km.
+    pub threshold_km: f64,
+    /// Coarse propagation step, seconds.
+    pub step_secs: f64,
+    /// Object sizes and uncertainties for collision probabilities.
+    pub collision: CollisionModel,
+}
+
+impl Default for ScreeningSettings {
+    fn default() -> Self {
+        Self {
+            window_days: 1.0,
+            threshold_km: 5.0,
+            step_secs: 60.0,
+            collision: CollisionModel::default(),
+        }
+    }
+}
+
+/// A close approach between two objects.
+#[derive(Debug, Clone)]
+pub struct Conjunction {
+    /// Index into `DebrisField::sats` (or the screened slice); the lower of
+    /// the two.
+    pub primary: usize,
+    pub secondary: usize,
+    /// Time of closest approach, full JD.
+    pub tca_jd: f64,
+    pub miss_km: f64,
+    pub relative_speed_km_s: f64,
+    /// Secondary relative to primary in the primary's radial / in-track /
+    /// cross-track frame at TCA, km.
+    pub ric_km: DVec3,
+    /// Sum of both objects' hard-body radii, m.
+    pub hard_body_m: f64,
+    /// Collision probability, if the encounter geometry allows one.
+    pub pc: Option<f64>,
+}
+
+impl Conjunction {
+    pub fn tca(&self) -> DateTime<Utc> {
+        datetime_from_jd(self.tca_jd, 0.0)
+    }
+}
+
+fn state_at(sat: &mut DebrisSat, jd_full: f64) -> Option<StateVector> {
+    let jd = jd_full.floor();
+    propagate(sat, jd, jd_full - jd).ok()
+}
+
+fn distance_km(a: &StateVector, b: &StateVector) -> f64 {
+    DVec3::from(a.r).distance(DVec3::from(b.r))
+}
+
+/// Pairs that may pass within `threshold_km` of each other before the next
+/// or since the last coarse step, with their distance: bucket positions
+/// into a grid of cubes sized for the fastest closing speed, compare each
+/// object against the 27 cells around it, and keep the pairs closer than
+/// the threshold plus half a step at their own relative speed. Co-orbiting
+/// objects barely move relative to each other, so a crowded shell yields
+/// few pairs.
+fn close_pairs(
+    states: &[Option<StateVector>],
+    threshold_km: f64,
+    step_secs: f64,
+) -> Vec<(usize, usize, f64)> {
+    let cell_km = threshold_km + MAX_RELATIVE_SPEED_KM_S * step_secs / 2.0;
+    let cell_of = |r: [f64; 3]| r.map(|x| (x / cell_km).floor() as i64);
+    let mut grid: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+    for (i, state) in states.iter().enumerate() {
+        if let Some(state) = state {
+            grid.entry(cell_of(state.r)).or_default().push(i);
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for (cell, members) in &grid {
+        for offset in 0..27 {
+            let neighbour = [
+                cell[0] + offset % 3 - 1,
+                cell[1] + offset / 3 % 3 - 1,
+                cell[2] + offset / 9 - 1,
+            ];
+            let Some(others) = grid.get(&neighbour) else {
+                continue;
+            };
+            for &a in members {
+                for &b in others {
+                    if a < b
+                        && let (Some(sa), Some(sb)) = (&states[a], &states[b])
+                    {
+                        let distance = distance_km(sa, sb);
+                        let speed = DVec3::from(sa.v).distance(DVec3::from(sb.v));
+                        let reach =
+                            threshold_km + speed.min(MAX_RELATIVE_SPEED_KM_S) * step_secs / 2.0;
+                        if distance < reach {
+                            pairs.push((a, b, distance));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    pairs
+}
+
+/// Finds the coarse steps where a pair's sampled distance is a local
+/// minimum, fed one step at a time. Only the pairs close at the latest step
+/// are held, so memory follows the busiest step rather than the window.
+#[derive(Default)]
+struct MinimumTracker {
+    /// Distance at the step before last (infinite if the pair wasn't close
+    /// then) and at the last step, per pair.
+    pairs: HashMap<(usize, usize), (f64, f64)>,
+}
+
+impl MinimumTracker {
+    /// Feed the pairs close at `step`, which follows the last step fed, and
+    /// return the `(a, b, step)` minima found at the step before. Steps a
+    /// pair wasn't close count as far; a tie goes to the later step, so a
+    /// flat pair of samples yields one minimum.
+    fn advance(
+        &mut self,
+        step: usize,
+        close: impl IntoIterator<Item = (usize, usize, f64)>,
+    ) -> Vec<(usize, usize, usize)> {
+        let mut current: HashMap<(usize, usize), f64> =
+            close.into_iter().map(|(a, b, d)| ((a, b), d)).collect();
+        let mut minima = Vec::new();
+        self.pairs.retain(|&(a, b), (before, last)| {
+            let next = current.remove(&(a, b)).unwrap_or(f64::INFINITY);
+            if *last <= *before && *last < next {
+                minima.push((a, b, step - 1));
+            }
+            (*before, *last) = (*last, next);
+            next.is_finite()
+        });
+        self.pairs.extend(
+            current
+                .into_iter()
+                .map(|(pair, distance)| (pair, (f64::INFINITY, distance))),
+        );
+        minima
+    }
+
+    /// Minima at `last_step`, the final step fed.
+    fn finish(mut self, last_step: usize) -> Vec<(usize, usize, usize)> {
+        self.advance(last_step + 1, [])
+    }
+}
+
+/// Time (full JD) and distance of the closest approach of `a` and `b` in
+/// `[start, end]`.
+fn refine(a: &DebrisSat, b: &DebrisSat, start: f64, end: f64) -> Option<(f64, f64)> {
+    let (mut a, mut b) = (a.clone(), b.clone());
+    let mut distance = |jd: f64| match (state_at(&mut a, jd), state_at(&mut b, jd)) {
+        (Some(sa), Some(sb)) => distance_km(&sa, &sb),
+        _ => f64::INFINITY,
+    };
+
+    let tca = golden_section_min(&mut distance, start, end, TCA_TOLERANCE_SECS / 86_400.0);
+    let miss = distance(tca);
+    miss.is_finite().then_some((tca, miss))
+}
+
+/// Radial, in-track and cross-track unit vectors of an orbit state, as the
+/// columns of a matrix.
+fn ric_basis(state: &StateVector) -> Option<DMat3> {
+    let (r, v) = (DVec3::from(state.r), DVec3::from(state.v));
+    let radial = r.try_normalize()?;
+    let cross_track = r.cross(v).try_normalize()?;
+    Some(DMat3::from_cols(
+        radial,
+        cross_track.cross(radial),
+        cross_track,
+    ))
+}
+
+/// Describe the approach of `secondary` to `primary` at `tca_jd`.
+fn conjunction_at(
+    sats: &[DebrisSat],
+    primary: usize,
+    secondary: usize,
+    tca_jd: f64,
+    model: &CollisionModel,
+) -> Option<Conjunction> {
+    let (a, b) = (&sats[primary], &sats[secondary]);
+    let p = state_at(&mut a.clone(), tca_jd)?;
+    let s = state_at(&mut b.clone(), tca_jd)?;
+    let dr = DVec3::from(s.r) - DVec3::from(p.r);
+    let dv = DVec3::from(s.v) - DVec3::from(p.v);
+
+    let ric = ric_basis(&p)?;
+    let covariance =
+        model.sigma(a, tca_jd).covariance(ric) + model.sigma(b, tca_jd).covariance(ric_basis(&s)?);
+    let hard_body_km = model.hard_body_km(a) + model.hard_body_km(b);
+    Some(Conjunction {
+        primary,
+        secondary,
+        tca_jd,
+        miss_km: dr.length(),
+        relative_speed_km_s: dv.length(),
+        ric_km: ric.transpose() * dr,
+        hard_body_m: hard_body_km * 1000.0,
+        pc: collision_probability(dr, dv, covariance, hard_body_km),
+    })
+}
+
+/// Screen every pair of objects for approaches closer than the threshold
+/// within the window starting at `start_jd` (full JD). Sorted by TCA.
+///
+/// Three stages: pairs whose perigee-apogee shells don't overlap are never
+/// compared; the rest are sieved at coarse steps with a spatial grid and
+/// each pair's relative speed, so no approach under the threshold can slip
+/// between two steps; and each local minimum of the sampled distance is
+/// refined with a golden-section search on the SGP4 distance, from the step
+/// before it to the step after. Each conjunction found gets a collision
+/// probability from `settings.collision`.
+pub fn screen(
+    sats: &mut [DebrisSat],
+    start_jd: f64,
+    settings: &ScreeningSettings,
+) -> Vec<Conjunction> {
+    let step_days = settings.step_secs / 86_400.0;
+    let steps = (settings.window_days / step_days).ceil() as usize;
+    let end_jd = start_jd + settings.window_days;
+    let shells: Vec<(f64, f64)> = sats
+        .iter()
+        .map(|sat| {
+            (
+                sat.elements.perigee_altitude_km() - SHELL_MARGIN_KM,
+                sat.elements.apogee_altitude_km() + SHELL_MARGIN_KM,
+            )
+        })
+        .collect();
+    let shells_overlap = |a: usize, b: usize| {
+        shells[a].0.max(shells[b].0) - shells[a].1.min(shells[b].1) <= settings.threshold_km
+    };
+
+    // A pair can stay close for hours (fragments of one breakup) and pass
+    // through several minima meanwhile, so each one is refined on its own.
+    let mut tracker = MinimumTracker::default();
+    let mut minima = Vec::new();
+    for step in 0..=steps {
+        let jd_full = start_jd + step as f64 * step_days;
+        let jd = jd_full.floor();
+        let states: Vec<Option<StateVector>> = propagate_parallel(sats, jd, jd_full - jd)
+            .into_iter()
+            .map(Result::ok)
+            .collect();
+        let close = close_pairs(&states, settings.threshold_km, settings.step_secs)
+            .into_iter()
+            .filter(|&(a, b, _)| shells_overlap(a, b));
+        minima.extend(tracker.advance(step, close));
+    }
+    minima.extend(tracker.finish(steps));
+
+    let brackets: Vec<(usize, usize, f64, f64)> = minima
+        .into_iter()
+        .map(|(a, b, step)| {
+            let start = start_jd + (step as f64 - 1.0) * step_days;
+            let end = start_jd + (step as f64 + 1.0) * step_days;
+            (a, b, start.max(start_jd), end.min(end_jd))
+        })
+        .collect();
+
+    let sats = &*sats;
+    let mut found: Vec<Conjunction> = brackets
+        .par_splat_map(ComputeTaskPool::get(), None, |_, chunk| {
+            chunk
+                .iter()
+                .filter_map(|&(a, b, start, end)| {
+                    let (tca, miss) = refine(&sats[a], &sats[b], start, end)?;
+                    if miss >= settings.threshold_km {
+                        return None;
+                    }
+                    conjunction_at(sats, a, b, tca, &settings.collision)
+                })
+                .collect::<Vec<_>>()
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+
+    found.sort_by(|x, y| x.tca_jd.total_cmp(&y.tca_jd));
+    found
+}
+
+/// Write conjunctions as CSV; `sats` is the slice they were screened from.
+pub fn write_conjunctions_csv(
+    out: impl Write,
+    conjunctions: &[Conjunction],
+    sats: &[DebrisSat],
+) -> Result<(), csv::Error> {
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record([
+        "TCA_UTC",
+        "PRIMARY_NORAD",
+        "PRIMARY_NAME",
+        "SECONDARY_NORAD",
+        "SECONDARY_NAME",
+        "MISS_KM",
+        "RELATIVE_SPEED_KM_S",
+        "RADIAL_KM",
+        "IN_TRACK_KM",
+        "CROSS_TRACK_KM",
+        "HARD_BODY_M",
+        "PC",
+    ])?;
+    for c in conjunctions {
+        let (p, s) = (&sats[c.primary], &sats[c.secondary]);
+        writer.write_record([
+            c.tca().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+            p.elements.catalog_number.to_string(),
+            p.name.clone().unwrap_or_default(),
+            s.elements.catalog_number.to_string(),
+            s.name.clone().unwrap_or_default(),
+            format!("{:.3}", c.miss_km),
+            format!("{:.3}", c.relative_speed_km_s),
+            format!("{:.3}", c.ric_km.x),
+            format!("{:.3}", c.ric_km.y),
+            format!("{:.3}", c.ric_km.z),
+            format!("{:.1}", c.hard_body_m),
+            c.pc.map(|pc| format!("{:.3e}", pc)).unwrap_or_default(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Headless screening: load the catalogs from disk, screen the window
+/// starting at `start` and print the conjunctions as CSV.
+pub fn run_screening(
+    catalogs: &[PathBuf],
+    start: DateTime<Utc>,
+    settings: &ScreeningSettings,
+) -> Result<usize, String> {
+    ComputeTaskPool::get_or_init(TaskPool::default);
+    let mut sats: Vec<DebrisSat> = read_catalogs(catalogs)?
+        .into_iter()
+        .map(|entry| DebrisSat {
+            name: entry.name,
+            elements: entry.elements,
+            satrec: entry.satrec,
+        })
+        .collect();
+
+    let conjunctions = screen(&mut sats, julian_date(start), settings);
+    write_conjunctions_csv(std::io::stdout().lock(), &conjunctions, &sats)
+        .map_err(|e| e.to_string())?;
+    Ok(conjunctions.len())
+}
+
+/// Screening results for the debris field, and the screening in progress.
+#[derive(Default, Resource)]
+pub struct Conjunctions {
+    pub results: Vec<Conjunction>,
+    /// `DebrisField::generation` the results (or the running task) are for.
+    pub generation: u64,
+    /// Screening running in the background, with the objects it works on.
+    task: Option<Task<(Vec<Conjunction>, Vec<DebrisSat>)>>,
+    /// Copy of the objects the results index into, for exporting.
+    screened: Vec<DebrisSat>,
+}
+
+/// Marker for the conjunction list panel.
+#[derive(Component)]
+pub struct ConjunctionPanel;
+
+/// A clickable row of the panel; the index into `Conjunctions::results`.
+#[derive(Component)]
+pub struct ConjunctionRow(pub usize);
+
+/// K screens the debris field over the window starting at the sim time, in
+/// the background. E exports the results as CSV.
+pub fn conjunction_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<ScreeningSettings>,
+    sim_time: Res<SimulationTime>,
+    debris_field: Res<DebrisField>,
+    mut conjunctions: ResMut<Conjunctions>,
+) {
+    if keys.just_pressed(KeyCode::KeyK) && conjunctions.task.is_none() {
+        let mut sats = debris_field.sats.clone();
+        let settings = settings.clone();
+        let start_jd = sim_time.jd + sim_time.fr;
+        info!(
+            "Screening {} objects over {:.1} days for approaches under {} km",
+            sats.len(),
+            settings.window_days,
+            settings.threshold_km
+        );
+        conjunctions.generation = debris_field.generation;
+        conjunctions.task = Some(AsyncComputeTaskPool::get().spawn(async move {
+            let results = screen(&mut sats, start_jd, &settings);
+            (results, sats)
+        }));
+    }
+
+    if keys.just_pressed(KeyCode::KeyE) && !conjunctions.results.is_empty() {
+        let path = format!("conjunctions_{}.csv", Utc::now().format("%Y%m%dT%H%M%S"));
+        let written = std::fs::File::create(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|file| {
+                write_conjunctions_csv(file, &conjunctions.results, &conjunctions.screened)
+                    .map_err(|e| e.to_string())
+            });
+        match written {
+            Ok(()) => info!(
+                "Exported {} conjunctions to {}",
+                conjunctions.results.len(),
+                path
+            ),
+            Err(e) => warn!("Couldn't export conjunctions to {}: {}", path, e),
+        }
+    }
+}
+
+/// Pick up finished screenings, and drop results when the field they index
+/// into is rebuilt.
+pub fn poll_screening(debris_field: Res<DebrisField>, mut conjunctions: ResMut<Conjunctions>) {
+    // Polling mustn't count as a change, or the panel rebuilds every frame.
+    let finished = conjunctions
+        .bypass_change_detection()
+        .task
+        .as_mut()
+        .and_then(check_ready);
+    if let Some((results, screened)) = finished {
+        info!("Screening found {} conjunctions", results.len());
+        conjunctions.task = None;
+        conjunctions.results = results;
+        conjunctions.screened = screened;
+    }
+    if conjunctions.generation != debris_field.generation && !conjunctions.results.is_empty() {
+        conjunctions.results.clear();
+        conjunctions.screened.clear();
+    }
+}
+
+/// Spawn the (empty) conjunction panel above the propagation counter.
+pub fn setup_conjunction_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Conjunction Panel"),
+        ConjunctionPanel,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(88.0),
+            left: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(2.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Interaction::default(),
+    ));
+}
+
+/// Rebuild the panel's rows when the results (or screening state) change.
+pub fn update_conjunction_panel(
+    mut commands: Commands,
+    conjunctions: Res<Conjunctions>,
+    panel: Single<Entity, With<ConjunctionPanel>>,
+) {
+    if !conjunctions.is_changed() {
+        return;
+    }
+    let running = conjunctions.task.is_some();
+
+    let font = TextFont {
+        font_size: 14.0,
+        ..default()
+    };
+    let title = if running {
+        "Screening for conjunctions...".to_string()
+    } else if conjunctions.results.is_empty() {
+        "K: screen for conjunctions".to_string()
+    } else {
+        format!(
+            "{} conjunctions (click to jump to TCA, E: export CSV)",
+            conjunctions.results.len()
+        )
+    };
+
+    commands
+        .entity(*panel)
+        .despawn_related::<Children>()
+        .with_children(|panel| {
+            panel.spawn((Text::new(title), font.clone(), TextColor(Color::WHITE)));
+            for (i, c) in conjunctions.results.iter().take(PANEL_ROWS).enumerate() {
+                let name = |index: usize| {
+                    conjunctions
+                        .screened
+                        .get(index)
+                        .map_or_else(String::new, |sat| {
+                            sat.name
+                                .clone()
+                                .unwrap_or_else(|| sat.elements.catalog_number.to_string())
+                        })
+                };
+                panel.spawn((
+                    ConjunctionRow(i),
+                    Button,
+                    Text::new(format!(
+                        "{}  {:.3} km  {:.2} km/s  Pc {}  {} / {}",
+                        c.tca().format("%m-%d %H:%M:%S"),
+                        c.miss_km,
+                        c.relative_speed_km_s,
+                        c.pc.map_or_else(|| "n/a".to_string(), |pc| format!("{:.1e}", pc)),
+                        name(c.primary),
+                        name(c.secondary)
+                    )),
+                    font.clone(),
+                    TextColor(Color::srgb(1.0, 0.85, 0.4)),
+                ));
+            }
+        });
+}
+
+/// Clicking a row pauses the sim clock at the TCA and selects the primary.
+pub fn jump_to_conjunction(
+    rows: Query<(&Interaction, &ConjunctionRow), Changed<Interaction>>,
+    conjunctions: Res<Conjunctions>,
+    debris: Query<(Entity, &Debris)>,
+    mut sim_time: ResMut<SimulationTime>,
+    mut selection: ResMut<Selection>,
+) {
+    for (interaction, row) in &rows {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(conjunction) = conjunctions.results.get(row.0) else {
+            continue;
+        };
+        sim_time.set_jd(conjunction.tca_jd);
+        sim_time.paused = true;
+        selection.selected = debris
+            .iter()
+            .find(|(_, d)| d.sat_index == conjunction.primary)
+            .map(|(entity, _)| entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed one pair's `(step, distance)` samples through a tracker over
+    /// steps `0..=last_step` and collect its minima.
+    fn local_minima(samples: &[(usize, f64)], last_step: usize) -> Vec<usize> {
+        let mut tracker = MinimumTracker::default();
+        let mut minima = Vec::new();
+        for step in 0..=last_step {
+            let close = samples
+                .iter()
+                .filter(|&&(s, _)| s == step)
+                .map(|&(_, d)| (0, 1, d));
+            minima.extend(tracker.advance(step, close));
+        }
+        minima.extend(tracker.finish(last_step));
+        minima.into_iter().map(|(_, _, step)| step).collect()
+    }
+
+    #[test]
+    fn finds_each_minimum_of_a_long_run() {
+        // Two approaches within one run of close steps, then a separate one.
+        let samples = [
+            (3, 40.0),
+            (4, 12.0),
+            (5, 30.0),
+            (6, 45.0),
+            (7, 20.0),
+            (8, 35.0),
+            (12, 50.0),
+        ];
+        assert_eq!(local_minima(&samples, 20), [4, 7, 12]);
+    }
+
+    #[test]
+    fn run_edges_and_ties() {
+        // Falling into the end of a run, and rising from its start.
+        assert_eq!(local_minima(&[(0, 10.0), (1, 20.0)], 5), [0]);
+        assert_eq!(local_minima(&[(5, 20.0), (6, 10.0)], 9), [6]);
+        // Still close at the end of the window.
+        assert_eq!(local_minima(&[(8, 20.0), (9, 10.0)], 9), [9]);
+        // A flat bottom is refined once.
+        assert_eq!(
+            local_minima(&[(2, 30.0), (3, 10.0), (4, 10.0), (5, 30.0)], 9),
+            [4]
+        );
+        assert!(local_minima(&[], 9).is_empty());
+    }
+
+    /// `n` objects 2 km apart along one circular equatorial orbit, `angle`
+    /// radians further round it.
+    fn cluster(n: usize, angle: f64) -> Vec<Option<StateVector>> {
+        let (radius, speed) = (7_000.0, 7.546);
+        (0..n)
+            .map(|i| {
+                let theta = angle + i as f64 * 2.0 / radius;
+                let (sin, cos) = theta.sin_cos();
+                Some(StateVector {
+                    r: [radius * cos, radius * sin, 0.0],
+                    v: [-speed * sin, speed * cos, 0.0],
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dense_coplanar_cluster_stays_small() {
+        // 1,000 objects in 2,000 km of one orbit: a few hundred share each
+        // 485 km cell, but they barely move relative to each other.
+        let n = 1_000;
+        let step_secs = 60.0;
+        let pairs = close_pairs(&cluster(n, 0.0), 5.0, step_secs);
+        assert!(pairs.iter().all(|&(_, _, d)| d < 10.0), "{pairs:?}");
+        assert!(pairs.len() <= 2 * n, "{} pairs", pairs.len());
+
+        // The tracker holds the pairs close at the latest step, however long
+        // the cluster is followed.
+        let mut tracker = MinimumTracker::default();
+        for step in 0..20 {
+            let states = cluster(n, step as f64 * 1e-3);
+            tracker.advance(step, close_pairs(&states, 5.0, step_secs));
+            assert!(tracker.pairs.len() <= 2 * n, "{}", tracker.pairs.len());
+        }
+    }
+}
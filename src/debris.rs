@@ -1,133 +1,307 @@
 use bevy::prelude::*;
-use chrono::{Datelike, Timelike, Utc};
+use chrono::{DateTime, Datelike, Timelike, Utc};
 
-use crate::loader::load_tles_to_sat_rec;
+use crate::elements::MeanElements;
+use crate::instancing::DebrisRendering;
+use crate::loader::TleCatalog;
+use crate::propagation::PropagationStatus;
+use crate::sun::Illumination;
 use SGP4_Rust::ext::jday;
 use SGP4_Rust::propagation::SatRec;
 
 pub const EARTH_RADIUS_KM: f64 = 6378.137;
 pub const KM_TO_WORLD: f32 = (1.0 / EARTH_RADIUS_KM) as f32;
+/// Default size of a debris marker, world units.
+pub const DEBRIS_RADIUS: f32 = 0.03;
 
 #[derive(Component)]
 pub struct Debris {
     pub sat_index: usize,
 }
+
+/// How a debris object is drawn.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DebrisStyle {
+    pub color: Color,
+    /// World units.
+    pub radius: f32,
+}
+
+impl Default for DebrisStyle {
+    fn default() -> Self {
+        Self {
+            color: Color::srgb(0.9, 0.2, 0.2),
+            radius: DEBRIS_RADIUS,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct DebrisSat {
+    /// Object name from a 3LE title line or OMM record, if the catalog had one.
+    pub name: Option<String>,
+    /// The elements `satrec` was initialised from.
+    pub elements: MeanElements,
     pub satrec: SatRec,
 }
 
 #[derive(Resource)]
 pub struct DebrisField {
     pub sats: Vec<DebrisSat>,
+    /// Bumped every time `sats` is rebuilt.
+    pub generation: u64,
 }
 
+/// The simulation clock. Time is accumulated frame by frame (see
+/// `time_control::advance_simulation_time`) so the scale can change at any
+/// moment without objects jumping.
 #[derive(Resource)]
 pub struct SimulationTime {
-    /// Integer part of JD at app start.
-    pub base_jd: f64,
-    /// Fractional part of JD at app start.
-    pub base_fr: f64,
-    /// How fast sim time runs vs real time (1.0 = real time).
+    /// Integer part of the current sim JD.
+    pub jd: f64,
+    /// Fractional part of the current sim JD. Kept separate from `jd` so
+    /// sub-second steps don't get lost in the large integer part.
+    pub fr: f64,
+    /// Full JD the simulation started at.
+    pub start_jd: f64,
+    /// How fast sim time runs vs real time (1.0 = real time, negative runs
+    /// backwards).
     pub time_scale: f64,
+    pub paused: bool,
+}
+
+impl SimulationTime {
+    pub fn new(start: DateTime<Utc>, time_scale: f64) -> Self {
+        let start_jd = julian_date(start);
+        let mut sim_time = Self {
+            jd: 0.0,
+            fr: 0.0,
+            start_jd,
+            time_scale,
+            paused: false,
+        };
+        sim_time.set_jd(start_jd);
+        sim_time
+    }
+
+    pub fn set_jd(&mut self, jd_full: f64) {
+        self.jd = jd_full.floor();
+        self.fr = jd_full - self.jd;
+    }
+
+    pub fn set_utc(&mut self, time: DateTime<Utc>) {
+        self.set_jd(julian_date(time));
+    }
+
+    /// Move the clock by `secs` of sim time (negative goes back).
+    pub fn advance_secs(&mut self, secs: f64) {
+        self.fr += secs / 86_400.0;
+        let whole_days = self.fr.floor();
+        self.jd += whole_days;
+        self.fr -= whole_days;
+    }
+
+    pub fn utc(&self) -> DateTime<Utc> {
+        datetime_from_jd(self.jd, self.fr)
+    }
 }
 
-pub fn setup_simulation_time(mut commands: Commands) {
-    let now = Utc::now();
+/// Map a TEME position (km) into world space: Earth radii, with TEME Z (the
+/// pole) pointing up along world Y. This is a proper rotation (TEME Y goes to
+/// world -Z), so anything Earth-fixed lines up with the rotated Earth mesh.
+pub fn teme_to_world(r_km: &[f64]) -> Vec3 {
+    let x = r_km[0] as f32;
+    let y = r_km[2] as f32;
+    let z = -r_km[1] as f32;
 
-    let year = now.year() as i32;
-    let month = now.month() as i32;
-    let day = now.day() as i32;
+    Vec3::new(x, y, z) * KM_TO_WORLD
+}
 
-    let hour = now.hour() as i32;
-    let minute = now.minute() as i32;
-    let second = now.second() as i32;
-    let sec_f = second as f64 + now.nanosecond() as f64 * 1e-9;
+/// Rotation taking TEME axes to world axes, as used by [`teme_to_world`].
+pub fn teme_to_world_rotation() -> Quat {
+    Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)
+}
+
+/// Initial clock settings, from the command line.
+#[derive(Resource)]
+pub struct SimulationConfig {
+    /// Sim time at app start; `None` means "now".
+    pub start_epoch: Option<DateTime<Utc>>,
+    pub time_scale: f64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            start_epoch: None,
+            time_scale: 1.0, // 1× real time
+        }
+    }
+}
+
+/// Full Julian date of a UTC instant.
+pub fn julian_date(time: DateTime<Utc>) -> f64 {
+    let sec_f = time.second() as f64 + time.nanosecond() as f64 * 1e-9;
 
     // Your `jday` returns a single f64: full Julian date in days.
-    let jd_full = jday(year, month, day, hour, minute, sec_f);
+    jday(
+        time.year(),
+        time.month() as i32,
+        time.day() as i32,
+        time.hour() as i32,
+        time.minute() as i32,
+        sec_f,
+    )
+}
 
-    let base_jd = jd_full.floor();
-    let base_fr = jd_full - base_jd;
+/// UTC instant of a Julian date given as integer + fractional parts.
+pub fn datetime_from_jd(jd: f64, fr: f64) -> DateTime<Utc> {
+    // JD 2440587.5 is the Unix epoch.
+    let days = (jd - 2_440_587.5) + fr;
+    let secs = days * 86_400.0;
+    let whole_secs = secs.floor();
+    let nanos = ((secs - whole_secs) * 1e9) as u32;
+    DateTime::from_timestamp(whole_secs as i64, nanos).unwrap_or_default()
+}
 
-    commands.insert_resource(SimulationTime {
-        base_jd,
-        base_fr,
-        time_scale: 1.0, // 1× real time
-    });
+pub fn setup_simulation_time(mut commands: Commands, config: Res<SimulationConfig>) {
+    let start = config.start_epoch.unwrap_or_else(Utc::now);
+    commands.insert_resource(SimulationTime::new(start, config.time_scale));
+}
+
+/// Asset paths of the catalogs the debris field is built from.
+#[derive(Resource)]
+pub struct CatalogSources(pub Vec<String>);
+
+impl Default for CatalogSources {
+    fn default() -> Self {
+        Self(vec!["tle_sample.txt".to_string()])
+    }
+}
+
+/// Shared render assets for debris points plus the handles of the catalogs
+/// the field is built from.
+#[derive(Resource)]
+pub struct DebrisAssets {
+    pub catalogs: Vec<Handle<TleCatalog>>,
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
 }
 
 pub fn setup_debris_field(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    sources: Res<CatalogSources>,
 ) {
-    // TODO - Update to asset loader.
-    let sats = load_tles_to_sat_rec("assets/tle_sample.txt")
-        .iter()
-        .map(|sat| DebrisSat {
-            satrec: sat.clone(),
-        })
-        .collect::<Vec<_>>();
-    let sat_length = sats.len();
-
-    commands.insert_resource(DebrisField { sats });
+    commands.insert_resource(DebrisField {
+        sats: Vec::new(),
+        generation: 0,
+    });
 
-    // Shared mesh / material for debris points.
-    let debris_mesh = meshes.add(Sphere::new(0.03).mesh().uv(8, 4));
+    // Shared mesh / material for debris points (per-entity rendering only).
+    let debris_mesh = meshes.add(Sphere::new(DEBRIS_RADIUS).mesh().uv(8, 4));
     let debris_material = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.9, 0.2, 0.2),
+        base_color: DebrisStyle::default().color,
         unlit: true,
         ..default()
     });
 
+    commands.insert_resource(DebrisAssets {
+        catalogs: sources
+            .0
+            .iter()
+            .map(|path| asset_server.load(path.clone()))
+            .collect(),
+        mesh: debris_mesh,
+        material: debris_material,
+    });
+}
+
+/// (Re)build the debris field whenever a catalog finishes loading or is
+/// changed on disk (hot reload). Catalogs that haven't loaded yet are left out
+/// and picked up by the rebuild their own load triggers.
+pub fn spawn_debris_from_catalog(
+    mut commands: Commands,
+    mut events: MessageReader<AssetEvent<TleCatalog>>,
+    catalogs: Res<Assets<TleCatalog>>,
+    debris_assets: Res<DebrisAssets>,
+    rendering: Res<DebrisRendering>,
+    mut debris_field: ResMut<DebrisField>,
+    existing: Query<Entity, With<Debris>>,
+) {
+    let mut reloaded = false;
+    for event in events.read() {
+        // In-memory catalogs (e.g. synthetic populations) only ever get
+        // `Added`; loaded ones get it too, but are logged on load.
+        let (AssetEvent::Added { id }
+        | AssetEvent::LoadedWithDependencies { id }
+        | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        if !debris_assets.catalogs.iter().any(|h| h.id() == *id) {
+            continue;
+        }
+        reloaded = true;
+        if matches!(event, AssetEvent::Added { .. }) {
+            continue;
+        }
+
+        if let Some(catalog) = catalogs.get(*id) {
+            let summary = &catalog.summary;
+            for diagnostic in &summary.diagnostics {
+                warn!("{}", diagnostic);
+            }
+            info!(
+                "Loaded {} objects ({} skipped, {} checksum failures)",
+                summary.loaded, summary.skipped, summary.checksum_failures
+            );
+        }
+    }
+    if !reloaded {
+        return;
+    }
+
+    for entity in &existing {
+        commands.entity(entity).despawn();
+    }
+
+    debris_field.sats = debris_assets
+        .catalogs
+        .iter()
+        .filter_map(|handle| catalogs.get(handle))
+        .flat_map(|catalog| &catalog.entries)
+        .map(|entry| DebrisSat {
+            name: entry.name.clone(),
+            elements: entry.elements.clone(),
+            satrec: entry.satrec.clone(),
+        })
+        .collect();
+    debris_field.generation += 1;
+
     // Spawn an entity per satellite.
-    for i in 0..sat_length {
-        commands.spawn((
-            Name::new(format!("Debris {}", i)),
+    for (i, sat) in debris_field.sats.iter().enumerate() {
+        let name = match &sat.name {
+            Some(name) => name.clone(),
+            None => format!("Debris {}", sat.elements.catalog_number),
+        };
+        let mut entity = commands.spawn((
+            Name::new(name),
             Debris { sat_index: i },
-            Mesh3d(debris_mesh.clone()),
-            MeshMaterial3d(debris_material.clone()),
+            DebrisStyle::default(),
+            PropagationStatus::default(),
+            Illumination::default(),
             Transform::default(),
             GlobalTransform::default(),
+            Visibility::default(),
         ));
-    }
-}
-
-pub fn update_debris_positions(
-    time: Res<Time>,
-    sim_time: Res<SimulationTime>,
-    mut debris_field: ResMut<DebrisField>,
-    mut query: Query<(&Debris, &mut Transform)>,
-) {
-    // Real seconds since app start
-    let elapsed_secs = time.elapsed().as_secs_f64();
-
-    // Convert to days and apply time scale (1.0 = real time)
-    let delta_days = (elapsed_secs / 86_400.0) * sim_time.time_scale;
-
-    // Full JD (integer + fractional) at this frame
-    let jd_full = (sim_time.base_jd + sim_time.base_fr) + delta_days;
-    let jd = jd_full.floor();
-    let fr = jd_full - jd;
-
-    for (debris, mut transform) in &mut query {
-        if let Some(debris_sat) = debris_field.sats.get_mut(debris.sat_index) {
-            let satrec = &mut debris_sat.satrec;
-
-            let (_err, r_km, _v_km_s) = match satrec.sgp4(jd, fr) {
-                Ok(d) => d,
-                Err(e) => {
-                    eprintln!("Error parsing TLE: {}", e);
-                    continue;
-                }
-            };
-
-            let x = r_km[0] as f32;
-            let y = r_km[2] as f32;
-            let z = r_km[1] as f32;
-
-            let pos = Vec3::new(x, y, z) * KM_TO_WORLD;
-            transform.translation = pos;
+        if *rendering == DebrisRendering::Entities {
+            entity.insert((
+                Mesh3d(debris_assets.mesh.clone()),
+                MeshMaterial3d(debris_assets.material.clone()),
+            ));
         }
     }
 }
@@ -0,0 +1,142 @@
+use bevy::{
+    ecs::system::SystemParam,
+    pbr::{ExtendedMaterial, MaterialExtension},
+    prelude::*,
+    render::render_resource::AsBindGroup,
+    shader::ShaderRef,
+};
+use std::f64::consts::PI;
+
+use crate::debris::{SimulationTime, teme_to_world_rotation};
+use crate::frames::{PolarMotion, gmst, teme_to_itrf};
+
+const SHADER_ASSET_PATH: &str = "shaders/earth.wgsl";
+
+/// Luminance of the night-lights texture at full white, nits.
+pub const NIGHT_LIGHTS_NITS: f32 = 400.0;
+
+/// Marker for the Earth mesh.
+#[derive(Component)]
+pub struct Earth;
+
+/// The Earth's surface: a lit standard material, plus city lights on the
+/// night side when a texture is given.
+pub type EarthMaterial = ExtendedMaterial<StandardMaterial, NightLights>;
+
+/// Emissive night-side texture, faded out across the terminator of the
+/// scene's directional light.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+pub struct NightLights {
+    /// Nits at full white; 0 turns the lights off.
+    #[uniform(100)]
+    pub brightness: f32,
+    #[texture(101)]
+    #[sampler(102)]
+    pub texture: Option<Handle<Image>>,
+}
+
+impl MaterialExtension for NightLights {
+    fn fragment_shader() -> ShaderRef {
+        SHADER_ASSET_PATH.into()
+    }
+}
+
+/// Asset path of the night-lights texture, if one was given.
+#[derive(Debug, Default, Resource)]
+pub struct NightLightsSource(pub Option<String>);
+
+/// Frame the scene is drawn in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource, clap::ValueEnum)]
+pub enum DisplayFrame {
+    /// TEME axes fixed in the world: orbits hold still, the Earth spins.
+    #[default]
+    Inertial,
+    /// ITRF axes fixed in the world: the Earth holds still and orbits sweep
+    /// around it, so geostationary objects sit still.
+    EarthFixed,
+}
+
+/// Orientation of the Earth-fixed (ITRF) frame at the current sim time.
+#[derive(Debug, Default, Resource)]
+pub struct EarthRotation {
+    /// Greenwich mean sidereal time, radians. TEME is tied to the mean
+    /// equinox, so this (not GAST) is the angle between TEME and PEF.
+    pub gmst: f64,
+    pub polar_motion: PolarMotion,
+}
+
+impl EarthRotation {
+    /// ITRF position of a TEME vector at an arbitrary time, with this polar
+    /// motion.
+    pub fn teme_to_itrf(&self, r: [f64; 3], jd: f64, fr: f64) -> [f64; 3] {
+        teme_to_itrf(r, jd, fr, self.polar_motion)
+    }
+
+    /// Inertial world-space orientation of the ITRF axes at the given
+    /// sidereal time.
+    fn itrf_to_inertial(&self, gmst: f64) -> Quat {
+        let pm = self.polar_motion;
+        // Small-angle polar motion: ITRF -> PEF tilts by yp about X and by
+        // xp about Y.
+        let polar = Quat::from_rotation_x(-pm.yp as f32) * Quat::from_rotation_y(-pm.xp as f32);
+        teme_to_world_rotation() * Quat::from_rotation_z(gmst as f32) * polar
+    }
+}
+
+/// The display frame together with the Earth's orientation: everything
+/// needed to place inertial (`teme_to_world`) or Earth-fixed vectors in the
+/// scene.
+#[derive(SystemParam)]
+pub struct SceneFrame<'w> {
+    pub frame: Res<'w, DisplayFrame>,
+    pub rotation: Res<'w, EarthRotation>,
+}
+
+impl SceneFrame<'_> {
+    /// Rotation from inertial world space into the displayed scene, now.
+    pub fn inertial_to_display(&self) -> Quat {
+        self.inertial_to_display_at(self.rotation.gmst)
+    }
+
+    /// Rotation from inertial world space into the displayed scene at the
+    /// given sidereal time. In the Earth-fixed frame the ITRF axes are held
+    /// where the inertial ones are at GMST 0.
+    pub fn inertial_to_display_at(&self, gmst: f64) -> Quat {
+        match *self.frame {
+            DisplayFrame::Inertial => Quat::IDENTITY,
+            DisplayFrame::EarthFixed => {
+                teme_to_world_rotation() * self.rotation.itrf_to_inertial(gmst).inverse()
+            }
+        }
+    }
+
+    /// Displayed orientation of the ITRF axes, now.
+    pub fn itrf_to_display(&self) -> Quat {
+        match *self.frame {
+            DisplayFrame::Inertial => self.rotation.itrf_to_inertial(self.rotation.gmst),
+            DisplayFrame::EarthFixed => teme_to_world_rotation(),
+        }
+    }
+}
+
+/// F switches between the inertial and Earth-fixed display frames.
+pub fn toggle_display_frame(keys: Res<ButtonInput<KeyCode>>, mut frame: ResMut<DisplayFrame>) {
+    if keys.just_pressed(KeyCode::KeyF) {
+        *frame = match *frame {
+            DisplayFrame::Inertial => DisplayFrame::EarthFixed,
+            DisplayFrame::EarthFixed => DisplayFrame::Inertial,
+        };
+    }
+}
+
+/// Track sidereal time from the sim clock.
+pub fn update_earth_rotation(sim_time: Res<SimulationTime>, mut rotation: ResMut<EarthRotation>) {
+    rotation.gmst = gmst(sim_time.jd, sim_time.fr);
+}
+
+/// Orient the Earth mesh for the current time and display frame. The UV
+/// sphere has its poles on local Z and longitude 0 at the middle of the
+/// texture, i.e. on local -X, hence the extra half turn.
+pub fn rotate_earth(scene: SceneFrame, mut earth: Single<&mut Transform, With<Earth>>) {
+    earth.rotation = scene.itrf_to_display() * Quat::from_rotation_z(PI as f32);
+}
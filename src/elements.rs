@@ -0,0 +1,219 @@
+use SGP4_Rust::propagation::SatRec;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use std::f64::consts::TAU;
+
+use crate::debris::EARTH_RADIUS_KM;
+
+/// Earth's gravitational parameter, km³/s² (WGS-84).
+pub const MU_EARTH_KM3_S2: f64 = 398600.4418;
+
+/// SGP4 mean elements for one object, independent of the format they were
+/// read from (TLE, OMM, CelesTrak GP). Field names and units follow the OMM
+/// `meanElements` / `tleParameters` blocks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeanElements {
+    /// NORAD catalog number. OMM allows 9 digits, so this may not fit a TLE.
+    pub catalog_number: u32,
+    /// International designator in OMM form, e.g. `1998-067A`.
+    pub object_id: String,
+    pub classification: char,
+    pub epoch: DateTime<Utc>,
+    /// rev/day
+    pub mean_motion: f64,
+    pub eccentricity: f64,
+    /// Degrees.
+    pub inclination: f64,
+    /// Right ascension of the ascending node, degrees.
+    pub raan: f64,
+    /// Argument of perigee, degrees.
+    pub arg_of_pericenter: f64,
+    /// Degrees.
+    pub mean_anomaly: f64,
+    /// 1 / Earth radii.
+    pub bstar: f64,
+    /// rev/day², as printed in TLE line 1.
+    pub mean_motion_dot: f64,
+    /// rev/day³, as printed in TLE line 1.
+    pub mean_motion_ddot: f64,
+    pub element_set_no: u32,
+    pub rev_at_epoch: u32,
+}
+
+impl MeanElements {
+    /// Orbital period in minutes.
+    pub fn period_minutes(&self) -> f64 {
+        1_440.0 / self.mean_motion
+    }
+
+    /// Semi-major axis in km, from the mean motion.
+    pub fn semi_major_axis_km(&self) -> f64 {
+        let n_rad_s = self.mean_motion * TAU / 86_400.0;
+        (MU_EARTH_KM3_S2 / (n_rad_s * n_rad_s)).cbrt()
+    }
+
+    /// Perigee altitude above the equatorial radius, km.
+    pub fn perigee_altitude_km(&self) -> f64 {
+        self.semi_major_axis_km() * (1.0 - self.eccentricity) - EARTH_RADIUS_KM
+    }
+
+    /// Apogee altitude above the equatorial radius, km.
+    pub fn apogee_altitude_km(&self) -> f64 {
+        self.semi_major_axis_km() * (1.0 + self.eccentricity) - EARTH_RADIUS_KM
+    }
+
+    /// Build a `SatRec` from these elements. SGP4 is only initialised from
+    /// TLE text, so the elements are formatted back into a pair of lines
+    /// first; this rounds them to TLE precision.
+    pub fn to_satrec(&self) -> SatRec {
+        let (line_1, line_2) = self.to_tle_lines();
+        SatRec::twoline2rv(&line_1, &line_2, "wgs84")
+    }
+
+    /// Format the elements as TLE lines 1 and 2, checksums included.
+    ///
+    /// The catalog number field is only five digits wide, so larger numbers
+    /// are written modulo 100000. SGP4 never reads it; the real number lives
+    /// in [`MeanElements::catalog_number`].
+    pub fn to_tle_lines(&self) -> (String, String) {
+        let satnum = self.catalog_number % 100_000;
+
+        let epoch_day = self.epoch.ordinal() as f64
+            + self.epoch.num_seconds_from_midnight() as f64 / 86_400.0
+            + self.epoch.nanosecond() as f64 * 1e-9 / 86_400.0;
+
+        let line_1 = format!(
+            "1 {:05}{} {:<8} {:02}{:012.8} {} {} {} 0 {:>4}",
+            satnum,
+            self.classification,
+            tle_designator(&self.object_id),
+            self.epoch.year() % 100,
+            epoch_day,
+            format_decimal_field(self.mean_motion_dot),
+            format_exponent_field(self.mean_motion_ddot),
+            format_exponent_field(self.bstar),
+            self.element_set_no % 10_000,
+        );
+
+        let line_2 = format!(
+            "2 {:05} {:8.4} {:8.4} {:07} {:8.4} {:8.4} {:11.8}{:>5}",
+            satnum,
+            self.inclination,
+            self.raan,
+            (self.eccentricity * 1e7).round() as u32,
+            self.arg_of_pericenter,
+            self.mean_anomaly,
+            self.mean_motion,
+            self.rev_at_epoch % 100_000,
+        );
+
+        (with_checksum(line_1), with_checksum(line_2))
+    }
+}
+
+/// Modulo-10 TLE checksum of a line's first 68 columns: digits count at face
+/// value, `-` counts as 1.
+pub fn tle_checksum(line: &str) -> u32 {
+    line.chars()
+        .take(68)
+        .map(|c| match c {
+            '-' => 1,
+            c => c.to_digit(10).unwrap_or(0),
+        })
+        .sum::<u32>()
+        % 10
+}
+
+fn with_checksum(line: String) -> String {
+    let checksum = tle_checksum(&line);
+    format!("{}{}", line, checksum)
+}
+
+/// `1998-067A` -> `98067A`.
+fn tle_designator(object_id: &str) -> String {
+    match object_id.split_once('-') {
+        Some((year, rest)) if year.len() == 4 => format!("{}{}", &year[2..], rest),
+        _ => object_id.chars().take(8).collect(),
+    }
+}
+
+/// `-0.00015910` -> `-.00015910` (10 columns, leading sign or space).
+fn format_decimal_field(value: f64) -> String {
+    let sign = if value < 0.0 { '-' } else { ' ' };
+    let digits = format!("{:.8}", value.abs().min(0.999_999_99));
+    format!("{}{}", sign, &digits[1..])
+}
+
+/// Assumed-decimal exponent notation: `0.29318e-3` -> ` 29318-3`. The
+/// exponent is a single digit, so magnitudes under `0.5e-14` are written as
+/// zero and those over `0.99999e9` as `99999+9`.
+fn format_exponent_field(value: f64) -> String {
+    let sign = if value < 0.0 { '-' } else { ' ' };
+    let abs = value.abs();
+    if abs == 0.0 {
+        return " 00000-0".to_string();
+    }
+    let mut exponent = abs.log10().floor() as i32 + 1;
+    let mut mantissa = (abs / 10f64.powi(exponent) * 1e5).round() as u32;
+    if mantissa >= 100_000 {
+        mantissa /= 10;
+        exponent += 1;
+    }
+    if exponent < -9 {
+        // Shift the mantissa right to keep the value, down to zero.
+        mantissa = (abs / 10f64.powi(-9) * 1e5).round() as u32;
+        exponent = -9;
+        if mantissa == 0 {
+            return " 00000-0".to_string();
+        }
+    } else if exponent > 9 {
+        mantissa = 99_999;
+        exponent = 9;
+    }
+    let exponent_sign = if exponent < 0 { '-' } else { '+' };
+    format!("{}{:05}{}{}", sign, mantissa, exponent_sign, exponent.abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::parse_exponent_field;
+
+    fn round_trip(value: f64) -> f64 {
+        let field = format_exponent_field(value);
+        assert_eq!(field.len(), 8, "{value:e} written as {field:?}");
+        parse_exponent_field(&field, "B*").unwrap()
+    }
+
+    #[test]
+    fn exponent_field_round_trips() {
+        for value in [
+            0.0,
+            0.29318e-3,
+            -0.11606e-4,
+            1.0e-5,
+            0.99999e-1,
+            0.999996e-4,
+            3.2e-9,
+            -4.1e-10,
+        ] {
+            let parsed = round_trip(value);
+            assert!(
+                (parsed - value).abs() <= value.abs() * 1e-5,
+                "{value:e} came back as {parsed:e}"
+            );
+        }
+        assert_eq!(format_exponent_field(0.29318e-3), " 29318-3");
+        assert_eq!(format_exponent_field(-0.11606e-4), "-11606-4");
+    }
+
+    #[test]
+    fn exponent_field_out_of_range() {
+        // Below 1e-10 the mantissa is shifted to fit the `-9` exponent,
+        // then flushed to zero.
+        assert_eq!(format_exponent_field(1.234e-11), " 01234-9");
+        assert_eq!(format_exponent_field(-5.0e-13), "-00050-9");
+        assert_eq!(format_exponent_field(1.0e-16), " 00000-0");
+        assert!((round_trip(1.234e-11) - 1.234e-11).abs() < 1e-15);
+        assert_eq!(format_exponent_field(3.0e12), " 99999+9");
+    }
+}
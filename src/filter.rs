@@ -0,0 +1,760 @@
+//! A small query language for picking objects out of the catalog, e.g.
+//!
+//! ```text
+//! inc > 95 and alt_perigee < 600 and type = DEBRIS
+//! name ~ "COSMOS 2251"
+//! not (regime = LEO or owner = US)
+//! ```
+//!
+//! A query is comparisons (`field op value`) joined with `and`, `or` and
+//! `not`, with parentheses for grouping; `and` binds tighter than `or`.
+//! Keywords and text comparisons are case-insensitive. Values are numbers,
+//! bare words (`DEBRIS`, `1993-036`, `R/B`) or double-quoted strings.
+//!
+//! Operators: `=`, `!=`, `<`, `<=`, `>`, `>=`, and `~` (text contains).
+//! Text fields compare lexicographically with `<` / `>`, which suits dates
+//! (`launch >= 2020-01-01`) and international designators. Comparisons on a
+//! field the object has no value for (SATCAT fields without a SATCAT) are
+//! false, as are `light` comparisons where the eclipse state isn't known
+//! (the headless `--list`).
+
+use std::fmt;
+
+use crate::coloring::OrbitRegime;
+use crate::elements::MeanElements;
+use crate::satcat::{ObjectType, SatelliteMetadata};
+use crate::sun::Illumination;
+
+/// Everything a query can look at for one object.
+#[derive(Debug, Clone, Copy)]
+pub struct Subject<'a> {
+    pub name: Option<&'a str>,
+    pub elements: &'a MeanElements,
+    pub metadata: Option<&'a SatelliteMetadata>,
+    /// Eclipse state at the sim time, when known.
+    pub illumination: Option<Illumination>,
+}
+
+/// Fields a query can refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Name,
+    /// International designator.
+    Id,
+    /// NORAD catalog number.
+    Norad,
+    Inclination,
+    Eccentricity,
+    Raan,
+    ArgOfPericenter,
+    MeanAnomaly,
+    MeanMotion,
+    Period,
+    SemiMajorAxis,
+    PerigeeAltitude,
+    ApogeeAltitude,
+    Bstar,
+    Epoch,
+    Regime,
+    Type,
+    Owner,
+    LaunchDate,
+    LaunchSite,
+    DecayDate,
+    Rcs,
+    /// Sunlit, penumbra or umbra.
+    Light,
+}
+
+/// Every field name, aliases included.
+const FIELDS: &[(&str, Field)] = &[
+    ("name", Field::Name),
+    ("id", Field::Id),
+    ("norad", Field::Norad),
+    ("inc", Field::Inclination),
+    ("ecc", Field::Eccentricity),
+    ("raan", Field::Raan),
+    ("argp", Field::ArgOfPericenter),
+    ("ma", Field::MeanAnomaly),
+    ("mm", Field::MeanMotion),
+    ("period", Field::Period),
+    ("sma", Field::SemiMajorAxis),
+    ("alt_perigee", Field::PerigeeAltitude),
+    ("perigee", Field::PerigeeAltitude),
+    ("alt_apogee", Field::ApogeeAltitude),
+    ("apogee", Field::ApogeeAltitude),
+    ("bstar", Field::Bstar),
+    ("epoch", Field::Epoch),
+    ("regime", Field::Regime),
+    ("type", Field::Type),
+    ("owner", Field::Owner),
+    ("launch", Field::LaunchDate),
+    ("site", Field::LaunchSite),
+    ("decay", Field::DecayDate),
+    ("rcs", Field::Rcs),
+    ("light", Field::Light),
+];
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        FIELDS
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, field)| *field)
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            Field::Norad
+                | Field::Inclination
+                | Field::Eccentricity
+                | Field::Raan
+                | Field::ArgOfPericenter
+                | Field::MeanAnomaly
+                | Field::MeanMotion
+                | Field::Period
+                | Field::SemiMajorAxis
+                | Field::PerigeeAltitude
+                | Field::ApogeeAltitude
+                | Field::Bstar
+                | Field::Rcs
+        )
+    }
+
+    fn number(self, subject: &Subject) -> Option<f64> {
+        let e = subject.elements;
+        Some(match self {
+            Field::Norad => e.catalog_number as f64,
+            Field::Inclination => e.inclination,
+            Field::Eccentricity => e.eccentricity,
+            Field::Raan => e.raan,
+            Field::ArgOfPericenter => e.arg_of_pericenter,
+            Field::MeanAnomaly => e.mean_anomaly,
+            Field::MeanMotion => e.mean_motion,
+            Field::Period => e.period_minutes(),
+            Field::SemiMajorAxis => e.semi_major_axis_km(),
+            Field::PerigeeAltitude => e.perigee_altitude_km(),
+            Field::ApogeeAltitude => e.apogee_altitude_km(),
+            Field::Bstar => e.bstar,
+            Field::Rcs => subject.metadata?.rcs?,
+            _ => return None,
+        })
+    }
+
+    fn text(self, subject: &Subject) -> Option<String> {
+        let metadata = subject.metadata;
+        Some(match self {
+            Field::Name => subject
+                .name
+                .or_else(|| metadata.map(|m| m.name.as_str()))?
+                .to_string(),
+            Field::Id => subject.elements.object_id.clone(),
+            Field::Epoch => subject
+                .elements
+                .epoch
+                .format("%Y-%m-%dT%H:%M:%S")
+                .to_string(),
+            Field::Regime => OrbitRegime::of(subject.elements).label().to_string(),
+            Field::Type => type_keyword(
+                metadata.map_or_else(|| ObjectType::from_name(subject.name), |m| m.object_type),
+            )
+            .to_string(),
+            Field::Owner => metadata?.owner.clone(),
+            Field::LaunchDate => metadata?.launch_date?.to_string(),
+            Field::LaunchSite => metadata?.launch_site.clone(),
+            Field::DecayDate => metadata?.decay_date?.to_string(),
+            Field::Light => light_keyword(subject.illumination?).to_string(),
+            _ => return None,
+        })
+    }
+}
+
+fn type_keyword(object_type: ObjectType) -> &'static str {
+    match object_type {
+        ObjectType::Payload => "PAYLOAD",
+        ObjectType::RocketBody => "ROCKET_BODY",
+        ObjectType::Debris => "DEBRIS",
+        ObjectType::Unknown => "UNKNOWN",
+    }
+}
+
+/// Accept SATCAT codes and a few spellings for `type` values.
+fn normalize_type(value: &str) -> Option<&'static str> {
+    let object_type = match value.to_ascii_uppercase().as_str() {
+        "PAYLOAD" | "PAY" => ObjectType::Payload,
+        "ROCKET_BODY" | "ROCKET" | "R/B" | "RB" => ObjectType::RocketBody,
+        "DEBRIS" | "DEB" => ObjectType::Debris,
+        "UNKNOWN" | "UNK" => ObjectType::Unknown,
+        _ => return None,
+    };
+    Some(type_keyword(object_type))
+}
+
+fn light_keyword(illumination: Illumination) -> &'static str {
+    match illumination {
+        Illumination::Sunlit => "SUNLIT",
+        Illumination::Penumbra => "PENUMBRA",
+        Illumination::Umbra => "UMBRA",
+    }
+}
+
+/// Accept `light` values; `shadow` and `eclipsed` mean the umbra.
+fn normalize_light(value: &str) -> Option<&'static str> {
+    let illumination = match value.to_ascii_uppercase().as_str() {
+        "SUNLIT" | "LIT" | "SUN" => Illumination::Sunlit,
+        "PENUMBRA" => Illumination::Penumbra,
+        "UMBRA" | "SHADOW" | "ECLIPSED" => Illumination::Umbra,
+        _ => return None,
+    };
+    Some(light_keyword(illumination))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+impl Op {
+    fn compare<T: PartialOrd>(self, a: &T, b: &T) -> bool {
+        match self {
+            Op::Eq => a == b,
+            Op::Ne => a != b,
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Gt => a > b,
+            Op::Ge => a >= b,
+            Op::Contains => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(f64),
+    /// Upper-cased, as text comparisons are case-insensitive.
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Compare { field: Field, op: Op, value: Value },
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn matches(&self, subject: &Subject) -> bool {
+        match self {
+            Expr::Compare { field, op, value } => match value {
+                Value::Number(b) => field.number(subject).is_some_and(|a| op.compare(&a, b)),
+                Value::Text(b) => field.text(subject).is_some_and(|a| {
+                    let a = a.to_uppercase();
+                    match op {
+                        Op::Contains => a.contains(b.as_str()),
+                        _ => op.compare(&a, b),
+                    }
+                }),
+            },
+            Expr::Not(inner) => !inner.matches(subject),
+            Expr::And(a, b) => a.matches(subject) && b.matches(subject),
+            Expr::Or(a, b) => a.matches(subject) || b.matches(subject),
+        }
+    }
+
+    fn refers_to(&self, wanted: Field) -> bool {
+        match self {
+            Expr::Compare { field, .. } => *field == wanted,
+            Expr::Not(inner) => inner.refers_to(wanted),
+            Expr::And(a, b) | Expr::Or(a, b) => a.refers_to(wanted) || b.refers_to(wanted),
+        }
+    }
+}
+
+/// A parsed query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    source: String,
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn parse(source: &str) -> Result<Self, FilterError> {
+        Self::parse_source(source).map_err(|error| error.locate(source))
+    }
+
+    fn parse_source(source: &str) -> Result<Self, FilterError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            next: 0,
+            end: source.len(),
+        };
+        let expr = parser.or()?;
+        if let Some(token) = parser.peek() {
+            return Err(FilterError::new(
+                token.at,
+                "expected `and`, `or` or the end",
+            ));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    pub fn matches(&self, subject: &Subject) -> bool {
+        self.expr.matches(subject)
+    }
+
+    /// Whether the result depends on the eclipse state, which changes with
+    /// sim time.
+    pub fn depends_on_light(&self) -> bool {
+        self.expr.refers_to(Field::Light)
+    }
+
+    /// The query text this was parsed from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Why a query didn't parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterError {
+    /// Byte offset into the query.
+    pub at: usize,
+    /// 1-based column of `at`, counted in characters.
+    pub column: usize,
+    pub message: String,
+}
+
+impl FilterError {
+    fn new(at: usize, message: impl Into<String>) -> Self {
+        Self {
+            at,
+            column: at + 1,
+            message: message.into(),
+        }
+    }
+
+    /// Work out the column in `source`, which may not be ASCII.
+    fn locate(mut self, source: &str) -> Self {
+        self.column = source
+            .char_indices()
+            .take_while(|&(i, _)| i < self.at)
+            .count()
+            + 1;
+        self
+    }
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.message)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    /// Bare word: field name, keyword or unquoted value.
+    Word(String),
+    Quoted(String),
+    Op(Op),
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// Byte offset into the query.
+    at: usize,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '+' | '/' | ':')
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(at, c)) = chars.peek() {
+        let kind = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => {
+                chars.next();
+                TokenKind::Open
+            }
+            ')' => {
+                chars.next();
+                TokenKind::Close
+            }
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, c)) => text.push(c),
+                        None => return Err(FilterError::new(at, "unterminated string")),
+                    }
+                }
+                TokenKind::Quoted(text)
+            }
+            '=' | '!' | '<' | '>' | '~' => {
+                chars.next();
+                let followed_by_eq = chars.next_if(|(_, c)| *c == '=').is_some();
+                let op = match (c, followed_by_eq) {
+                    ('=', _) => Op::Eq,
+                    ('!', true) => Op::Ne,
+                    ('<', false) => Op::Lt,
+                    ('<', true) => Op::Le,
+                    ('>', false) => Op::Gt,
+                    ('>', true) => Op::Ge,
+                    ('~', false) => Op::Contains,
+                    _ => return Err(FilterError::new(at, "unknown operator")),
+                };
+                TokenKind::Op(op)
+            }
+            c if is_word_char(c) => {
+                let mut word = String::new();
+                while let Some((_, c)) = chars.next_if(|(_, c)| is_word_char(*c)) {
+                    word.push(c);
+                }
+                TokenKind::Word(word)
+            }
+            c => return Err(FilterError::new(at, format!("unexpected `{}`", c))),
+        };
+        tokens.push(Token { kind, at });
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the token list.
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+    /// Offset reported for errors at the end of the query.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.next).cloned();
+        self.next += 1;
+        token
+    }
+
+    /// Consume the next token if it's the keyword `keyword`.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let is_keyword = matches!(
+            self.peek(),
+            Some(Token { kind: TokenKind::Word(word), .. }) if word.eq_ignore_ascii_case(keyword)
+        );
+        if is_keyword {
+            self.next += 1;
+        }
+        is_keyword
+    }
+
+    fn or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.and()?;
+        while self.keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.unary()?;
+        while self.keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, FilterError> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if matches!(
+            self.peek(),
+            Some(Token {
+                kind: TokenKind::Open,
+                ..
+            })
+        ) {
+            self.next += 1;
+            let expr = self.or()?;
+            return match self.advance() {
+                Some(Token {
+                    kind: TokenKind::Close,
+                    ..
+                }) => Ok(expr),
+                Some(token) => Err(FilterError::new(token.at, "expected `)`")),
+                None => Err(FilterError::new(self.end, "missing `)`")),
+            };
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, FilterError> {
+        let (field, field_at) = match self.advance() {
+            Some(Token {
+                kind: TokenKind::Word(name),
+                at,
+            }) => match Field::from_name(&name) {
+                Some(field) => (field, at),
+                None => {
+                    let names: Vec<_> = FIELDS.iter().map(|(name, _)| *name).collect();
+                    return Err(FilterError::new(
+                        at,
+                        format!("unknown field `{}` (one of {})", name, names.join(", ")),
+                    ));
+                }
+            },
+            Some(token) => return Err(FilterError::new(token.at, "expected a field name")),
+            None => return Err(FilterError::new(self.end, "expected a field name")),
+        };
+        let op = match self.advance() {
+            Some(Token {
+                kind: TokenKind::Op(op),
+                ..
+            }) => op,
+            Some(token) => return Err(FilterError::new(token.at, "expected an operator")),
+            None => return Err(FilterError::new(self.end, "expected an operator")),
+        };
+        let (text, value_at) = match self.advance() {
+            Some(Token {
+                kind: TokenKind::Word(text) | TokenKind::Quoted(text),
+                at,
+            }) => (text, at),
+            Some(token) => return Err(FilterError::new(token.at, "expected a value")),
+            None => return Err(FilterError::new(self.end, "expected a value")),
+        };
+
+        let value = if field.is_numeric() {
+            if op == Op::Contains {
+                return Err(FilterError::new(field_at, "`~` only works on text fields"));
+            }
+            let number = text
+                .parse::<f64>()
+                .map_err(|_| FilterError::new(value_at, format!("`{}` is not a number", text)))?;
+            Value::Number(number)
+        } else if field == Field::Type {
+            let keyword = normalize_type(&text).ok_or_else(|| {
+                FilterError::new(value_at, "expected PAYLOAD, ROCKET_BODY, DEBRIS or UNKNOWN")
+            })?;
+            Value::Text(keyword.to_string())
+        } else if field == Field::Light {
+            let keyword = normalize_light(&text)
+                .ok_or_else(|| FilterError::new(value_at, "expected SUNLIT, PENUMBRA or UMBRA"))?;
+            Value::Text(keyword.to_string())
+        } else {
+            Value::Text(text.to_uppercase())
+        };
+        Ok(Expr::Compare { field, op, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    /// A COSMOS 2251 debris fragment: LEO, 74°, 800 km.
+    fn elements() -> MeanElements {
+        MeanElements {
+            catalog_number: 34427,
+            object_id: "1993-036SX".to_string(),
+            classification: 'U',
+            epoch: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+            mean_motion: 14.3,
+            eccentricity: 0.001,
+            inclination: 74.0,
+            raan: 10.0,
+            arg_of_pericenter: 20.0,
+            mean_anomaly: 30.0,
+            bstar: 1e-4,
+            mean_motion_dot: 0.0,
+            mean_motion_ddot: 0.0,
+            element_set_no: 999,
+            rev_at_epoch: 1,
+        }
+    }
+
+    fn subject(elements: &MeanElements) -> Subject<'_> {
+        Subject {
+            name: Some("COSMOS 2251 DEB"),
+            elements,
+            metadata: None,
+            illumination: None,
+        }
+    }
+
+    fn matches(query: &str, subject: &Subject) -> bool {
+        Filter::parse(query)
+            .unwrap_or_else(|e| panic!("{query}: {e}"))
+            .matches(subject)
+    }
+
+    fn error_at(query: &str) -> usize {
+        Filter::parse(query).unwrap_err().at
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let elements = elements();
+        let s = subject(&elements);
+        // true or (false and false)
+        assert!(matches("inc > 70 or inc < 10 and ecc > 0.5", &s));
+        // (true or false) and false
+        assert!(!matches("(inc > 70 or inc < 10) and ecc > 0.5", &s));
+        assert!(!matches("not inc > 70", &s));
+        assert!(matches("not (inc < 10 or ecc > 0.5)", &s));
+        assert!(matches("not not inc > 70", &s));
+    }
+
+    #[test]
+    fn keywords_and_fields_ignore_case() {
+        let elements = elements();
+        let s = subject(&elements);
+        assert!(matches("INC > 70 AND Regime = leo", &s));
+        assert!(matches("Not (Type = payload) Or name ~ xyz", &s));
+        assert!(matches("name ~ cosmos", &s));
+    }
+
+    #[test]
+    fn quoted_values_keep_spaces() {
+        let elements = elements();
+        let s = subject(&elements);
+        assert!(matches(r#"name ~ "COSMOS 2251""#, &s));
+        assert!(!matches(r#"name = "COSMOS 2251""#, &s));
+        assert!(matches(r#"name = "cosmos 2251 deb""#, &s));
+        assert!(matches("id = 1993-036SX and id >= 1993-036", &s));
+    }
+
+    #[test]
+    fn type_accepts_aliases() {
+        let elements = elements();
+        let s = subject(&elements);
+        assert!(matches("type = DEB", &s));
+        assert!(matches("type = debris", &s));
+        assert!(matches("type != R/B", &s));
+        assert_eq!(
+            Filter::parse("type = R/B").unwrap(),
+            Filter {
+                source: "type = R/B".to_string(),
+                expr: Expr::Compare {
+                    field: Field::Type,
+                    op: Op::Eq,
+                    value: Value::Text("ROCKET_BODY".to_string()),
+                },
+            }
+        );
+        assert_eq!(error_at("type = SATELLITE"), 7);
+    }
+
+    #[test]
+    fn numeric_fields_reject_contains_and_words() {
+        let error = Filter::parse("inc ~ 9").unwrap_err();
+        assert_eq!(error.at, 0);
+        assert!(error.message.contains("text fields"), "{}", error.message);
+        assert_eq!(error_at("alt_perigee < low"), 14);
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_column() {
+        let unknown = Filter::parse("inc > 1 and colour = red").unwrap_err();
+        assert_eq!(unknown.at, 12);
+        assert!(unknown.message.starts_with("unknown field `colour`"));
+        assert_eq!(unknown.to_string().split(':').next(), Some("column 13"));
+
+        // Columns count characters, not bytes.
+        let accented = Filter::parse(r#"name ~ "ÉTOILE" and colour = red"#).unwrap_err();
+        assert_eq!(accented.at, 21);
+        assert_eq!(accented.column, 21);
+        assert!(accented.to_string().starts_with("column 21:"));
+
+        let unclosed = Filter::parse("(inc > 1 or ecc < 0.1").unwrap_err();
+        assert_eq!(unclosed.at, 21);
+        assert_eq!(unclosed.message, "missing `)`");
+
+        let unterminated = Filter::parse(r#"name ~ "COSMOS"#).unwrap_err();
+        assert_eq!(unterminated.at, 7);
+        assert_eq!(unterminated.message, "unterminated string");
+
+        let trailing = Filter::parse("inc > 1 ecc < 0.1").unwrap_err();
+        assert_eq!(trailing.at, 8);
+        assert_eq!(error_at("inc > 1 )"), 8);
+        assert_eq!(error_at("inc >"), 5);
+    }
+
+    #[test]
+    fn satcat_fields_are_false_without_metadata() {
+        let elements = elements();
+        let s = subject(&elements);
+        assert!(!matches("owner = CIS", &s));
+        assert!(!matches("owner != CIS", &s));
+        assert!(!matches("rcs > 0", &s));
+        assert!(!matches("launch >= 1993-01-01", &s));
+
+        let metadata = SatelliteMetadata {
+            name: "COSMOS 2251 DEB".to_string(),
+            owner: "CIS".to_string(),
+            launch_date: chrono::NaiveDate::from_ymd_opt(1993, 6, 16),
+            launch_site: "PKMTR".to_string(),
+            object_type: ObjectType::Debris,
+            rcs: Some(0.05),
+            rcs_size: None,
+            decay_date: None,
+        };
+        let with_satcat = Subject {
+            metadata: Some(&metadata),
+            ..s
+        };
+        assert!(matches(
+            "owner = cis and rcs < 0.1 and launch >= 1993-01-01 and site ~ pkm",
+            &with_satcat
+        ));
+        assert!(!matches("decay > 2000-01-01", &with_satcat));
+    }
+
+    #[test]
+    fn light_compares_eclipse_state() {
+        let elements = elements();
+        let unknown = subject(&elements);
+        assert!(!matches("light = SUNLIT", &unknown));
+        assert!(!matches("light != SUNLIT", &unknown));
+
+        let eclipsed = Subject {
+            illumination: Some(Illumination::Umbra),
+            ..unknown
+        };
+        assert!(matches("light = umbra", &eclipsed));
+        assert!(matches("light = SHADOW", &eclipsed));
+        assert!(!matches("light = penumbra", &eclipsed));
+        assert_eq!(error_at("light = dark"), 8);
+
+        let filter = Filter::parse("inc > 10 and not light = SUNLIT").unwrap();
+        assert!(filter.depends_on_light());
+        assert!(!Filter::parse("inc > 10").unwrap().depends_on_light());
+    }
+}
@@ -0,0 +1,285 @@
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    input::{ButtonState, keyboard::Key, keyboard::KeyboardInput},
+    prelude::*,
+};
+
+use crate::coloring::OrbitRegime;
+use crate::debris::{Debris, DebrisField};
+use crate::filter::{Filter, Subject};
+use crate::loader::{TleEntry, read_catalogs};
+use crate::propagation::PropagationStatus;
+use crate::satcat::{SatelliteMetadata, parse_satcat};
+use crate::sun::Illumination;
+
+/// The query currently deciding which objects are shown.
+#[derive(Debug, Default, Resource)]
+pub struct ActiveFilter {
+    pub filter: Option<Filter>,
+    /// Objects matching it at the last evaluation.
+    pub matched: usize,
+}
+
+/// Marker for objects hidden by the active filter.
+#[derive(Component)]
+pub struct FilteredOut;
+
+/// State of the filter bar's text entry.
+#[derive(Debug, Default, Resource)]
+pub struct FilterInput {
+    /// Keyboard focus is on the bar; other shortcuts are suspended.
+    pub editing: bool,
+    pub text: String,
+    /// Parse error of the last submitted text.
+    pub error: Option<String>,
+}
+
+/// Marker for the filter bar text.
+#[derive(Component)]
+pub struct FilterBar;
+
+/// Objects whose filter result may have changed on their own.
+type NeedsRefilter = Or<(Added<Debris>, Changed<SatelliteMetadata>)>;
+
+/// What `apply_filter` reads and updates per object.
+type FilterSubjects = (
+    Entity,
+    &'static Debris,
+    Option<&'static SatelliteMetadata>,
+    Option<&'static Illumination>,
+    &'static PropagationStatus,
+    &'static mut Visibility,
+);
+
+/// `/` focuses the filter bar. While it has focus, typed text goes into the
+/// bar, Enter applies it (an empty query shows everything) and Escape
+/// cancels. Runs before `Update` and swallows all key presses while editing
+/// so other shortcuts don't fire.
+pub fn edit_filter(
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut typed: MessageReader<KeyboardInput>,
+    mut input: ResMut<FilterInput>,
+    mut active: ResMut<ActiveFilter>,
+) {
+    if !input.editing {
+        typed.clear();
+        if keys.just_pressed(KeyCode::Slash) {
+            input.editing = true;
+            input.text = active
+                .filter
+                .as_ref()
+                .map(|f| f.source().to_string())
+                .unwrap_or_default();
+            keys.reset_all();
+        }
+        return;
+    }
+
+    for key in typed.read() {
+        if key.state != ButtonState::Pressed {
+            continue;
+        }
+        match &key.logical_key {
+            Key::Enter => {
+                let text = input.text.trim();
+                if text.is_empty() {
+                    active.filter = None;
+                    input.error = None;
+                    input.editing = false;
+                    continue;
+                }
+                match Filter::parse(text) {
+                    Ok(filter) => {
+                        active.filter = Some(filter);
+                        input.error = None;
+                        input.editing = false;
+                    }
+                    Err(e) => input.error = Some(e.to_string()),
+                }
+            }
+            Key::Escape => {
+                input.editing = false;
+                input.error = None;
+            }
+            Key::Backspace => {
+                input.text.pop();
+            }
+            _ => {
+                if let Some(text) = &key.text {
+                    input.text.extend(text.chars().filter(|c| !c.is_control()));
+                }
+            }
+        }
+    }
+    keys.reset_all();
+}
+
+/// Re-evaluate the filter when it changes or objects appear or get new
+/// metadata, and when objects cross a shadow boundary if the filter looks at
+/// `light`. Objects are hidden when filtered out or not propagating, and
+/// shown otherwise.
+pub fn apply_filter(
+    mut commands: Commands,
+    mut active: ResMut<ActiveFilter>,
+    debris_field: Res<DebrisField>,
+    changed: Query<(), NeedsRefilter>,
+    relit: Query<(), Changed<Illumination>>,
+    mut removed: RemovedComponents<SatelliteMetadata>,
+    mut debris: Query<FilterSubjects>,
+) {
+    let metadata_removed = removed.read().count() > 0;
+    let light_changed =
+        !relit.is_empty() && active.filter.as_ref().is_some_and(|f| f.depends_on_light());
+    if !active.is_changed() && changed.is_empty() && !metadata_removed && !light_changed {
+        return;
+    }
+
+    let mut matched = 0;
+    for (entity, debris, metadata, illumination, status, mut visibility) in &mut debris {
+        let Some(sat) = debris_field.sats.get(debris.sat_index) else {
+            continue;
+        };
+        let subject = Subject {
+            name: sat.name.as_deref(),
+            elements: &sat.elements,
+            metadata,
+            illumination: illumination.copied(),
+        };
+        let shown = active.filter.as_ref().is_none_or(|f| f.matches(&subject));
+        if shown {
+            matched += 1;
+            commands.entity(entity).remove::<FilteredOut>();
+        } else {
+            commands.entity(entity).insert(FilteredOut);
+        }
+        let wanted = if shown && *status == PropagationStatus::Nominal {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        visibility.set_if_neq(wanted);
+    }
+    // Don't re-trigger ourselves through change detection.
+    active.bypass_change_detection().matched = matched;
+}
+
+/// Spawn the filter bar along the top of the window.
+pub fn setup_filter_bar(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Filter Bar"),
+        FilterBar,
+        Text::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Percent(30.0),
+            width: Val::Percent(40.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Interaction::default(),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    ));
+}
+
+pub fn update_filter_bar(
+    input: Res<FilterInput>,
+    active: Res<ActiveFilter>,
+    debris_field: Res<DebrisField>,
+    bar: Single<(&mut Text, &mut TextColor), With<FilterBar>>,
+) {
+    let (mut text, mut color) = bar.into_inner();
+    let total = debris_field.sats.len();
+
+    text.0 = if input.editing {
+        match &input.error {
+            Some(error) => format!("Filter: {}_\n{}", input.text, error),
+            None => format!("Filter: {}_", input.text),
+        }
+    } else {
+        match &active.filter {
+            Some(filter) => format!(
+                "Filter: {}  ({} of {} objects, / to edit)",
+                filter.source(),
+                active.matched,
+                total
+            ),
+            None => format!("{} objects  (/ to filter)", total),
+        }
+    };
+    color.0 = if input.error.is_some() {
+        Color::srgb(1.0, 0.6, 0.4)
+    } else {
+        Color::WHITE
+    };
+}
+
+/// Load the catalogs (and SATCAT, if any) directly from disk and return
+/// the entries matching `filter` (all, without one) with their metadata.
+pub fn read_matching(
+    catalogs: &[PathBuf],
+    satcat: Option<&Path>,
+    filter: Option<&Filter>,
+) -> Result<Vec<(TleEntry, Option<SatelliteMetadata>)>, String> {
+    let satcat = satcat
+        .map(|path| {
+            std::fs::read_to_string(path)
+                .map(|text| parse_satcat(path, &text))
+                .map_err(|e| format!("{}: {}", path.display(), e))
+        })
+        .transpose()?;
+
+    Ok(read_catalogs(catalogs)?
+        .into_iter()
+        .filter_map(|entry| {
+            let metadata = satcat
+                .as_ref()
+                .and_then(|s| s.records.get(&entry.elements.catalog_number))
+                .cloned();
+            let subject = Subject {
+                name: entry.name.as_deref(),
+                elements: &entry.elements,
+                metadata: metadata.as_ref(),
+                illumination: None,
+            };
+            filter
+                .is_none_or(|f| f.matches(&subject))
+                .then_some((entry, metadata))
+        })
+        .collect())
+}
+
+/// Headless listing: print the objects matching `filter` as tab-separated
+/// columns and return how many matched.
+pub fn list_matching(
+    catalogs: &[PathBuf],
+    satcat: Option<&Path>,
+    filter: Option<&Filter>,
+) -> Result<usize, String> {
+    let matching = read_matching(catalogs, satcat, filter)?;
+    println!("NORAD\tNAME\tID\tREGIME\tPERIGEE_KM\tAPOGEE_KM\tINC_DEG");
+    for (entry, metadata) in &matching {
+        let elements = &entry.elements;
+        println!(
+            "{}\t{}\t{}\t{}\t{:.1}\t{:.1}\t{:.4}",
+            elements.catalog_number,
+            entry
+                .name
+                .as_deref()
+                .or_else(|| metadata.as_ref().map(|m| m.name.as_str()))
+                .unwrap_or(""),
+            elements.object_id,
+            OrbitRegime::of(elements).label(),
+            elements.perigee_altitude_km(),
+            elements.apogee_altitude_km(),
+            elements.inclination
+        );
+    }
+    Ok(matching.len())
+}
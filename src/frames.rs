@@ -0,0 +1,239 @@
+//! Reference frame conversions for SGP4 output.
+//!
+//! SGP4 produces positions in TEME (True Equator, Mean Equinox). Going
+//! Earth-fixed takes two steps, following Vallado's `teme2ecef`:
+//!
+//! * TEME -> PEF (pseudo Earth-fixed): rotate about the pole by GMST.
+//! * PEF -> ITRF (i.e. ECEF): correct for polar motion, which is a few
+//!   metres at most and may be left at zero.
+//!
+//! Vectors are `[f64; 3]` in km, like `SatRec::sgp4` returns them.
+//! Times are Julian dates split into day and fraction, like
+//! `SimulationTime`; UTC stands in for UT1, which is off by under a second.
+
+use std::f64::consts::TAU;
+
+use crate::debris::EARTH_RADIUS_KM;
+
+/// WGS-84 flattening.
+pub const EARTH_FLATTENING: f64 = 1.0 / 298.257223563;
+
+/// Greenwich mean sidereal time in radians (IAU-82, as used by SGP4).
+pub fn gmst(jd: f64, fr: f64) -> f64 {
+    let t = (jd - 2_451_545.0 + fr) / 36_525.0;
+    let secs = -6.2e-6 * t * t * t
+        + 0.093104 * t * t
+        + (876600.0 * 3600.0 + 8640184.812866) * t
+        + 67310.54841;
+    // 240 seconds of sidereal time per degree.
+    (secs / 240.0).to_radians().rem_euclid(TAU)
+}
+
+/// Rotate a TEME vector into PEF.
+pub fn teme_to_pef(r: [f64; 3], gmst: f64) -> [f64; 3] {
+    let (sin, cos) = gmst.sin_cos();
+    [cos * r[0] + sin * r[1], -sin * r[0] + cos * r[1], r[2]]
+}
+
+/// Pole offsets of the ITRF pole from the PEF (celestial ephemeris) pole,
+/// radians. Published daily in the IERS Bulletin A; zero is within a few
+/// metres.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PolarMotion {
+    pub xp: f64,
+    pub yp: f64,
+}
+
+impl PolarMotion {
+    pub fn from_arcsec(xp: f64, yp: f64) -> Self {
+        Self {
+            xp: (xp / 3_600.0).to_radians(),
+            yp: (yp / 3_600.0).to_radians(),
+        }
+    }
+
+    /// Rows of the matrix taking ITRF to PEF (Vallado's IAU-76/FK5 `pm`).
+    fn matrix(&self) -> [[f64; 3]; 3] {
+        let (sin_x, cos_x) = self.xp.sin_cos();
+        let (sin_y, cos_y) = self.yp.sin_cos();
+        [
+            [cos_x, 0.0, -sin_x],
+            [sin_x * sin_y, cos_y, cos_x * sin_y],
+            [sin_x * cos_y, -sin_y, cos_x * cos_y],
+        ]
+    }
+}
+
+/// Rotate a PEF vector into ITRF.
+pub fn pef_to_itrf(r: [f64; 3], polar_motion: PolarMotion) -> [f64; 3] {
+    let m = polar_motion.matrix();
+    std::array::from_fn(|i| m[0][i] * r[0] + m[1][i] * r[1] + m[2][i] * r[2])
+}
+
+/// TEME -> ITRF (ECEF) at the given time.
+pub fn teme_to_itrf(r: [f64; 3], jd: f64, fr: f64, polar_motion: PolarMotion) -> [f64; 3] {
+    pef_to_itrf(teme_to_pef(r, gmst(jd, fr)), polar_motion)
+}
+
+/// WGS-84 geodetic coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geodetic {
+    /// Radians, north positive.
+    pub latitude: f64,
+    /// Radians, east positive.
+    pub longitude: f64,
+    pub altitude_km: f64,
+}
+
+impl Geodetic {
+    pub fn from_degrees(latitude: f64, longitude: f64, altitude_km: f64) -> Self {
+        Self {
+            latitude: latitude.to_radians(),
+            longitude: longitude.to_radians(),
+            altitude_km,
+        }
+    }
+
+    /// ITRF position (km) of these coordinates.
+    pub fn to_itrf(self) -> [f64; 3] {
+        let e2 = EARTH_FLATTENING * (2.0 - EARTH_FLATTENING);
+        let (sin_lat, cos_lat) = self.latitude.sin_cos();
+        let (sin_lon, cos_lon) = self.longitude.sin_cos();
+        let n = EARTH_RADIUS_KM / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+        [
+            (n + self.altitude_km) * cos_lat * cos_lon,
+            (n + self.altitude_km) * cos_lat * sin_lon,
+            (n * (1.0 - e2) + self.altitude_km) * sin_lat,
+        ]
+    }
+
+    /// Local east, north and up unit vectors, in ITRF.
+    pub fn enu_axes(self) -> [[f64; 3]; 3] {
+        let (sin_lat, cos_lat) = self.latitude.sin_cos();
+        let (sin_lon, cos_lon) = self.longitude.sin_cos();
+        [
+            [-sin_lon, cos_lon, 0.0],
+            [-sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat],
+            [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat],
+        ]
+    }
+
+    /// Geodetic coordinates of an ITRF position (km), iterating on the
+    /// latitude (converges to well under a metre in a few steps).
+    pub fn from_itrf(r: [f64; 3]) -> Self {
+        let e2 = EARTH_FLATTENING * (2.0 - EARTH_FLATTENING);
+        let p = r[0].hypot(r[1]);
+        let longitude = r[1].atan2(r[0]);
+
+        let mut latitude = r[2].atan2(p * (1.0 - e2));
+        let mut n = EARTH_RADIUS_KM;
+        for _ in 0..5 {
+            let sin = latitude.sin();
+            n = EARTH_RADIUS_KM / (1.0 - e2 * sin * sin).sqrt();
+            latitude = (r[2] + n * e2 * sin).atan2(p);
+        }
+
+        let altitude_km = if latitude.cos().abs() > 1e-9 {
+            p / latitude.cos() - n
+        } else {
+            r[2].abs() - n * (1.0 - e2)
+        };
+        Self {
+            latitude,
+            longitude,
+            altitude_km,
+        }
+    }
+}
+
+/// Direction and distance of a target as seen from a site on the ground.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LookAngles {
+    /// Radians clockwise from north.
+    pub azimuth: f64,
+    /// Radians above the horizon (the plane normal to the geodetic up).
+    pub elevation: f64,
+    pub range_km: f64,
+}
+
+impl LookAngles {
+    /// Look angles from `site` to the ITRF position `target` (km).
+    pub fn from_itrf(site: Geodetic, target: [f64; 3]) -> Self {
+        let origin = site.to_itrf();
+        let d: [f64; 3] = std::array::from_fn(|i| target[i] - origin[i]);
+        let [east, north, up] = site
+            .enu_axes()
+            .map(|axis| axis[0] * d[0] + axis[1] * d[1] + axis[2] * d[2]);
+        let range_km = (east * east + north * north + up * up).sqrt();
+        Self {
+            azimuth: east.atan2(north).rem_euclid(TAU),
+            elevation: (up / range_km).asin(),
+            range_km,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Vallado, *Fundamentals of Astrodynamics and Applications*, example
+    /// 3-15: 2004-04-06 07:51:28.386009 UTC, which is JD 2453101.827406783
+    /// in UT1.
+    const JD: f64 = 2_453_101.5;
+    const FR: f64 = 0.327_406_783;
+
+    fn assert_close(found: [f64; 3], expected: [f64; 3], tolerance_km: f64) {
+        for i in 0..3 {
+            assert!(
+                (found[i] - expected[i]).abs() < tolerance_km,
+                "{found:?}, expected {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn gmst_matches_vallado() {
+        let expected = 312.809_894_3_f64.to_radians();
+        assert!((gmst(JD, FR) - expected).abs() < 1e-8, "{}", gmst(JD, FR));
+        // J2000.0: 280.46061837°.
+        let j2000 = 280.460_618_37_f64.to_radians();
+        assert!((gmst(2_451_545.0, 0.0) - j2000).abs() < 1e-8);
+    }
+
+    #[test]
+    fn teme_to_itrf_matches_vallado() {
+        let teme = [5094.18016210, 6127.64465950, 6380.34453270];
+        let pef = teme_to_pef(teme, gmst(JD, FR));
+        assert_close(pef, [-1033.47503130, 7901.30558560, 6380.34453270], 1e-3);
+
+        let polar_motion = PolarMotion::from_arcsec(-0.140_682, 0.333_309);
+        let itrf = teme_to_itrf(teme, JD, FR, polar_motion);
+        assert_close(itrf, [-1033.4793830, 7901.2952754, 6380.3565958], 1e-3);
+    }
+
+    #[test]
+    fn geodetic_from_itrf_matches_vallado() {
+        // Example 3-3.
+        let geodetic = Geodetic::from_itrf([6524.834, 6862.875, 6448.296]);
+        assert!((geodetic.latitude.to_degrees() - 34.352_496).abs() < 1e-6);
+        assert!((geodetic.longitude.to_degrees() - 46.446_4).abs() < 1e-4);
+        assert!((geodetic.altitude_km - 5085.22).abs() < 1e-2);
+    }
+
+    #[test]
+    fn geodetic_round_trips() {
+        for (latitude, longitude, altitude_km) in [
+            (0.0, 0.0, 0.0),
+            (51.5, -0.13, 0.05),
+            (-33.9, 151.2, 400.0),
+            (89.999_999, 10.0, 1.0),
+            (-90.0, 0.0, 0.0),
+        ] {
+            let site = Geodetic::from_degrees(latitude, longitude, altitude_km);
+            let back = Geodetic::from_itrf(site.to_itrf());
+            assert_close(back.to_itrf(), site.to_itrf(), 1e-6);
+            assert!((back.altitude_km - altitude_km).abs() < 1e-6, "{back:?}");
+        }
+    }
+}
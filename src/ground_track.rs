@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+
+use crate::debris::{Debris, DebrisField, SimulationTime};
+use crate::earth::{EarthRotation, SceneFrame};
+use crate::orbits::Pinned;
+use crate::selection::Selection;
+
+/// Lift the track slightly off the surface so it doesn't z-fight the texture.
+const SURFACE_OFFSET: f32 = 1.003;
+
+/// Ground track toggle and extent.
+#[derive(Debug, Resource)]
+pub struct GroundTrackSettings {
+    pub enabled: bool,
+    /// Minutes of track drawn either side of the current sim time.
+    pub minutes: f64,
+    /// Minutes between track samples.
+    pub step_minutes: f64,
+}
+
+impl Default for GroundTrackSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            minutes: 90.0,
+            step_minutes: 0.5,
+        }
+    }
+}
+
+/// Sub-satellite points of a debris entity, as ITRF unit vectors.
+#[derive(Component)]
+pub struct GroundTrack {
+    pub points: Vec<Vec3>,
+    /// Index of the first point at or after `center_jd`; earlier points are
+    /// the past track.
+    pub now_index: usize,
+    /// Full JD the track is centred on.
+    pub center_jd: f64,
+}
+
+/// G toggles ground tracks.
+pub fn toggle_ground_track(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<GroundTrackSettings>,
+) {
+    if keys.just_pressed(KeyCode::KeyG) {
+        settings.enabled = !settings.enabled;
+    }
+}
+
+/// (Re)compute ground tracks for the selected and pinned objects while
+/// enabled, and drop them from everything else.
+pub fn update_ground_tracks(
+    mut commands: Commands,
+    settings: Res<GroundTrackSettings>,
+    sim_time: Res<SimulationTime>,
+    earth_rotation: Res<EarthRotation>,
+    selection: Res<Selection>,
+    mut debris_field: ResMut<DebrisField>,
+    mut debris: Query<(Entity, &Debris, Has<Pinned>, Option<&mut GroundTrack>)>,
+) {
+    let now_jd = sim_time.jd + sim_time.fr;
+    let step_days = settings.step_minutes.max(0.01) / 1_440.0;
+
+    for (entity, debris, pinned, track) in &mut debris {
+        let wanted = settings.enabled && (pinned || selection.selected == Some(entity));
+        if !wanted {
+            if track.is_some() {
+                commands.entity(entity).remove::<GroundTrack>();
+            }
+            continue;
+        }
+
+        // The track is Earth-fixed, so it only needs redoing once the clock
+        // has moved by a sample step.
+        if let Some(track) = &track
+            && (now_jd - track.center_jd).abs() < step_days
+        {
+            continue;
+        }
+
+        let Some(sat) = debris_field.sats.get_mut(debris.sat_index) else {
+            continue;
+        };
+        let steps = (settings.minutes / settings.step_minutes.max(0.01)).ceil() as i64;
+
+        let mut points = Vec::with_capacity(2 * steps as usize + 1);
+        let mut now_index = 0;
+        for i in -steps..=steps {
+            let jd_full = now_jd + i as f64 * step_days;
+            let jd = jd_full.floor();
+            let fr = jd_full - jd;
+            let Ok((_err, r_km, _v_km_s)) = sat.satrec.sgp4(jd, fr) else {
+                continue;
+            };
+            let r = earth_rotation.teme_to_itrf(r_km, jd, fr);
+            if i < 0 {
+                now_index += 1;
+            }
+            points.push(Vec3::new(r[0] as f32, r[1] as f32, r[2] as f32).normalize_or_zero());
+        }
+
+        let new_track = GroundTrack {
+            points,
+            now_index,
+            center_jd: now_jd,
+        };
+        match track {
+            Some(mut track) => *track = new_track,
+            None => {
+                commands.entity(entity).insert(new_track);
+            }
+        }
+    }
+}
+
+/// Draw the tracks on the Earth's surface at its current orientation: the
+/// past track dim, the future bright.
+pub fn draw_ground_tracks(scene: SceneFrame, tracks: Query<&GroundTrack>, mut gizmos: Gizmos) {
+    let rotation = scene.itrf_to_display();
+    let to_world = |p: &Vec3| rotation * *p * SURFACE_OFFSET;
+
+    for track in &tracks {
+        let split = track.now_index.min(track.points.len());
+        // Share the point at the split so the two halves join up.
+        let past = &track.points[..(split + 1).min(track.points.len())];
+        let future = &track.points[split..];
+        gizmos.linestrip(past.iter().map(to_world), Color::srgba(1.0, 0.6, 0.1, 0.5));
+        gizmos.linestrip(future.iter().map(to_world), Color::srgb(1.0, 0.6, 0.1));
+    }
+}
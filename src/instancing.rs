@@ -0,0 +1,165 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    camera::visibility::NoFrustumCulling,
+    mesh::{MeshVertexAttribute, MeshVertexBufferLayoutRef, PrimitiveTopology},
+    pbr::{MaterialPipeline, MaterialPipelineKey},
+    prelude::*,
+    render::{
+        render_resource::{
+            AsBindGroup, RenderPipelineDescriptor, ShaderType, SpecializedMeshPipelineError,
+            VertexFormat,
+        },
+        storage::ShaderStorageBuffer,
+    },
+    shader::ShaderRef,
+};
+
+use crate::debris::{Debris, DebrisField, DebrisStyle};
+
+const SHADER_ASSET_PATH: &str = "shaders/debris_cloud.wgsl";
+
+/// Index of the debris object a vertex belongs to.
+const ATTRIBUTE_INSTANCE_INDEX: MeshVertexAttribute =
+    MeshVertexAttribute::new("DebrisInstanceIndex", 514_392_117, VertexFormat::Uint32);
+
+/// Corners of a triangle circumscribing the unit disc.
+const TRIANGLE: [[f32; 3]; 3] = [
+    [0.0, 2.0, 0.0],
+    [-1.732_050_8, -1.0, 0.0],
+    [1.732_050_8, -1.0, 0.0],
+];
+
+/// How debris objects are drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource, clap::ValueEnum)]
+pub enum DebrisRendering {
+    /// One draw for the whole field, fed from a storage buffer.
+    #[default]
+    Instanced,
+    /// A sphere mesh entity per object. Slow past a few thousand objects,
+    /// kept for comparison.
+    Entities,
+}
+
+/// Per-object data as laid out in the storage buffer.
+#[derive(Debug, Default, Clone, Copy, ShaderType)]
+pub struct DebrisInstance {
+    /// xyz: world position, w: disc radius (0 hides the object).
+    pub position: Vec4,
+    /// Linear RGBA.
+    pub color: Vec4,
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct DebrisCloudMaterial {
+    #[storage(0, read_only)]
+    pub instances: Handle<ShaderStorageBuffer>,
+}
+
+impl Material for DebrisCloudMaterial {
+    fn vertex_shader() -> ShaderRef {
+        SHADER_ASSET_PATH.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        SHADER_ASSET_PATH.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let vertex_layout = layout.0.get_layout(&[
+            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+            ATTRIBUTE_INSTANCE_INDEX.at_shader_location(1),
+        ])?;
+        descriptor.vertex.buffers = vec![vertex_layout];
+        Ok(())
+    }
+}
+
+/// The single entity that draws the whole debris field in instanced mode.
+#[derive(Component)]
+pub struct DebrisCloud {
+    /// Number of objects the mesh was built for.
+    pub count: usize,
+    pub instances: Handle<ShaderStorageBuffer>,
+}
+
+/// One triangle per object; the shader expands it around the object's
+/// position. Never empty, see `setup_debris_cloud`.
+fn cloud_mesh(count: usize) -> Mesh {
+    let count = count.max(1);
+    let positions: Vec<[f32; 3]> = (0..count).flat_map(|_| TRIANGLE).collect();
+    let indices: Vec<u32> = (0..count as u32).flat_map(|i| [i; 3]).collect();
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(ATTRIBUTE_INSTANCE_INDEX, indices)
+}
+
+/// Spawn the (empty) debris cloud when rendering instanced.
+pub fn setup_debris_cloud(
+    mut commands: Commands,
+    rendering: Res<DebrisRendering>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<DebrisCloudMaterial>>,
+    mut buffers: ResMut<Assets<ShaderStorageBuffer>>,
+) {
+    if *rendering != DebrisRendering::Instanced {
+        return;
+    }
+
+    // wgpu rejects empty storage buffers, so there's always at least one
+    // (hidden) instance.
+    let instances = buffers.add(ShaderStorageBuffer::from(vec![DebrisInstance::default()]));
+    commands.spawn((
+        Name::new("Debris Cloud"),
+        DebrisCloud {
+            count: 0,
+            instances: instances.clone(),
+        },
+        Mesh3d(meshes.add(cloud_mesh(0))),
+        MeshMaterial3d(materials.add(DebrisCloudMaterial { instances })),
+        Transform::default(),
+        // The mesh's own vertices all sit around the origin.
+        NoFrustumCulling,
+    ));
+}
+
+/// Resize the cloud mesh when the debris field changes size, and write every
+/// object's position, size and color into the storage buffer.
+pub fn write_debris_instances(
+    cloud: Single<(&mut DebrisCloud, &mut Mesh3d)>,
+    debris_field: Res<DebrisField>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut buffers: ResMut<Assets<ShaderStorageBuffer>>,
+    debris: Query<(&Debris, &Transform, &InheritedVisibility, &DebrisStyle)>,
+) {
+    let (mut cloud, mut mesh) = cloud.into_inner();
+    let count = debris_field.sats.len();
+    if cloud.count != count {
+        mesh.0 = meshes.add(cloud_mesh(count));
+        cloud.count = count;
+    }
+
+    let Some(buffer) = buffers.get_mut(&cloud.instances) else {
+        return;
+    };
+    let mut instances = vec![DebrisInstance::default(); count.max(1)];
+    for (debris, transform, visibility, style) in &debris {
+        let Some(instance) = instances.get_mut(debris.sat_index) else {
+            continue;
+        };
+        let radius = if visibility.get() { style.radius } else { 0.0 };
+        *instance = DebrisInstance {
+            position: transform.translation.extend(radius),
+            color: style.color.to_linear().to_vec4(),
+        };
+    }
+    buffer.set_data(instances);
+}
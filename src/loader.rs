@@ -1,22 +1,659 @@
 use SGP4_Rust::propagation::SatRec;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use bevy::asset::{AssetLoader, LoadContext, io::Reader};
+use bevy::prelude::*;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
 
-pub fn load_tles_to_sat_rec(path: &str) -> Vec<SatRec> {
-    let file = File::open(path).expect("Cannot open TLE file");
-    let reader = BufReader::new(file);
+use crate::elements::{MeanElements, tle_checksum};
+use crate::omm::{self, CatalogFormat};
 
-    let mut sat_recs: Vec<SatRec> = Vec::new();
-    let mut line_vec: Vec<String> = Vec::new();
-    for line in reader.lines() {
-        let line = line.expect("Could not read line");
-        line_vec.push(line);
-        if line_vec.len() == 2 {
-            let tle_line_1 = line_vec.remove(0);
-            let tle_line_2 = line_vec.remove(0);
-            sat_recs.push(SatRec::twoline2rv(&*tle_line_1, &*tle_line_2, "wgs84"))
+/// Width of a well-formed element line, checksum digit included.
+const TLE_LINE_LEN: usize = 69;
+
+/// One catalog entry, whichever format it was read from.
+#[derive(Clone)]
+pub struct TleEntry {
+    /// Object name from a 3LE title line or OMM `OBJECT_NAME` (`None` for
+    /// plain 2LE).
+    pub name: Option<String>,
+    pub elements: MeanElements,
+    pub satrec: SatRec,
+}
+
+/// How the loader treats malformed records.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Strictness {
+    /// Fail the whole load on the first malformed record.
+    Strict,
+    /// Skip malformed records and report them in the summary.
+    #[default]
+    Lenient,
+    /// Like `Lenient`, but keep records whose only fault is a bad checksum.
+    Permissive,
+}
+
+/// What went wrong with a single record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TleFault {
+    /// Element line doesn't start with the expected `1 ` / `2 `.
+    LineNumber { expected: u8 },
+    /// Element line is not 69 columns wide.
+    LineLength { found: usize },
+    /// Modulo-10 checksum in column 69 doesn't match the line.
+    Checksum { expected: u32, found: char },
+    /// Line 1 and line 2 carry different catalog numbers.
+    CatalogMismatch { line1: u32, line2: u32 },
+    /// A field couldn't be parsed.
+    Field { name: &'static str, value: String },
+    /// A required OMM / GP field is absent.
+    MissingField { name: &'static str },
+    /// Line 1 was not followed by a line 2.
+    MissingLine2,
+    /// The XML / JSON / CSV document itself is malformed.
+    Syntax { message: String },
+}
+
+impl fmt::Display for TleFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TleFault::LineNumber { expected } => {
+                write!(f, "expected element line to start with `{} `", expected)
+            }
+            TleFault::LineLength { found } => {
+                write!(f, "expected {} columns, found {}", TLE_LINE_LEN, found)
+            }
+            TleFault::Checksum { expected, found } => {
+                write!(
+                    f,
+                    "checksum mismatch: computed {}, line has `{}`",
+                    expected, found
+                )
+            }
+            TleFault::CatalogMismatch { line1, line2 } => {
+                write!(
+                    f,
+                    "catalog number {} on line 1 but {} on line 2",
+                    line1, line2
+                )
+            }
+            TleFault::Field { name, value } => write!(f, "invalid {}: `{}`", name, value),
+            TleFault::MissingField { name } => write!(f, "missing {}", name),
+            TleFault::MissingLine2 => write!(f, "line 1 is not followed by a line 2"),
+            TleFault::Syntax { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Where in a catalog file a fault was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    /// 1-based line number (TLE, KVN, CSV, XML).
+    Line(usize),
+    /// 0-based record index, for formats without useful line numbers (JSON).
+    Record(usize),
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Line(line) => write!(f, "{}", line),
+            Location::Record(index) => write!(f, "record {}", index),
+        }
+    }
+}
+
+/// Errors produced while loading a catalog.
+#[derive(Debug)]
+pub enum TleError {
+    /// The catalog file couldn't be read at all.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A record failed validation.
+    Malformed {
+        path: PathBuf,
+        location: Location,
+        fault: TleFault,
+    },
+}
+
+impl fmt::Display for TleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TleError::Io { path, source } => {
+                write!(f, "{}: {}", path.display(), source)
+            }
+            TleError::Malformed {
+                path,
+                location,
+                fault,
+            } => {
+                write!(f, "{}:{}: {}", path.display(), location, fault)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TleError::Io { source, .. } => Some(source),
+            TleError::Malformed { .. } => None,
         }
     }
+}
+
+/// Counts (and the individual problems) from a load.
+#[derive(Debug, Default)]
+pub struct LoadSummary {
+    pub loaded: usize,
+    pub skipped: usize,
+    /// Records with a bad checksum, whether they were skipped or kept.
+    pub checksum_failures: usize,
+    /// Every malformed record that was skipped or kept in non-strict modes.
+    pub diagnostics: Vec<TleError>,
+}
+
+/// Parsed catalog plus the summary of what happened while reading it. Also
+/// the asset type produced by [`TleCatalogLoader`].
+#[derive(Asset, TypePath)]
+pub struct TleCatalog {
+    pub entries: Vec<TleEntry>,
+    pub summary: LoadSummary,
+}
+
+/// Per-asset settings for [`TleCatalogLoader`], settable from a `.meta` file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TleLoaderSettings {
+    pub strictness: Strictness,
+}
+
+/// Loads TLE (`.tle`, `.3le`, `.txt`), CCSDS OMM (`.xml`, `.kvn`, `.omm`) and
+/// CelesTrak GP (`.json`, `.csv`) catalogs as [`TleCatalog`] assets.
+#[derive(Default)]
+pub struct TleCatalogLoader;
+
+impl AssetLoader for TleCatalogLoader {
+    type Asset = TleCatalog;
+    type Settings = TleLoaderSettings;
+    type Error = TleError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &TleLoaderSettings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<TleCatalog, TleError> {
+        let path = load_context.path().to_path_buf();
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|source| TleError::Io {
+                path: path.clone(),
+                source,
+            })?;
+
+        let text = String::from_utf8_lossy(&bytes);
+        parse_catalog(&path, &text, settings.strictness)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tle", "3le", "txt", "xml", "kvn", "omm", "json", "csv"]
+    }
+}
+
+/// Catalog loaded when none is given on the command line, relative to the
+/// working directory (headless modes don't go through the asset server).
+const SAMPLE_CATALOG: &str = "assets/tle_sample.txt";
+
+/// Read and parse catalog files straight from disk, for the headless modes;
+/// the bundled sample if `paths` is empty. Malformed records are skipped.
+pub fn read_catalogs(paths: &[PathBuf]) -> Result<Vec<TleEntry>, String> {
+    let sample = [PathBuf::from(SAMPLE_CATALOG)];
+    let paths = if paths.is_empty() { &sample[..] } else { paths };
+
+    let mut entries = Vec::new();
+    for path in paths {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let catalog = parse_catalog(path, &text, Strictness::Lenient).map_err(|e| e.to_string())?;
+        entries.extend(catalog.entries);
+    }
+    Ok(entries)
+}
+
+/// Parse a catalog in any supported format, picked by extension or, failing
+/// that, by looking at the content.
+pub fn parse_catalog(
+    path: &Path,
+    text: &str,
+    strictness: Strictness,
+) -> Result<TleCatalog, TleError> {
+    match CatalogFormat::detect(path, text) {
+        CatalogFormat::Tle => parse_tles(path, text, strictness),
+        CatalogFormat::OmmXml => omm::parse_omm_xml(path, text, strictness),
+        CatalogFormat::OmmKvn => omm::parse_omm_kvn(path, text, strictness),
+        CatalogFormat::GpJson => omm::parse_gp_json(path, text, strictness),
+        CatalogFormat::GpCsv => omm::parse_gp_csv(path, text, strictness),
+    }
+}
+
+/// Accumulates entries and diagnostics while honouring a [`Strictness`].
+pub(crate) struct CatalogBuilder<'a> {
+    path: &'a Path,
+    strictness: Strictness,
+    catalog: TleCatalog,
+}
+
+impl<'a> CatalogBuilder<'a> {
+    pub(crate) fn new(path: &'a Path, strictness: Strictness) -> Self {
+        Self {
+            path,
+            strictness,
+            catalog: TleCatalog {
+                entries: Vec::new(),
+                summary: LoadSummary::default(),
+            },
+        }
+    }
+
+    pub(crate) fn push(&mut self, entry: TleEntry) {
+        self.catalog.entries.push(entry);
+        self.catalog.summary.loaded += 1;
+    }
+
+    /// Record a fault on a record that is being dropped.
+    pub(crate) fn reject(&mut self, location: Location, fault: TleFault) -> Result<(), TleError> {
+        self.report(location, fault)?;
+        self.catalog.summary.skipped += 1;
+        Ok(())
+    }
+
+    /// Record a fault without dropping anything. Still fatal in `Strict`.
+    pub(crate) fn report(&mut self, location: Location, fault: TleFault) -> Result<(), TleError> {
+        let err = TleError::Malformed {
+            path: self.path.to_path_buf(),
+            location,
+            fault,
+        };
+        if self.strictness == Strictness::Strict {
+            return Err(err);
+        }
+        self.catalog.summary.diagnostics.push(err);
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> TleCatalog {
+        self.catalog
+    }
+}
+
+/// Parse a 2LE or 3LE catalog. The format is detected per record, so files
+/// mixing both (or with blank lines between records) are fine. `path` is only
+/// used for diagnostics.
+pub fn parse_tles(path: &Path, text: &str, strictness: Strictness) -> Result<TleCatalog, TleError> {
+    let mut builder = CatalogBuilder::new(path, strictness);
+
+    let mut name: Option<String> = None;
+    // Pending line 1 and its (1-based) line number.
+    let mut tle_line_1: Option<(usize, &str)> = None;
 
-    sat_recs
+    for (idx, line) in text.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if line.starts_with("2 ")
+            && let Some((line_1_no, line_1)) = tle_line_1.take()
+        {
+            let name = name.take();
+            let elements = match parse_record(line_1_no, line_1, line_no, line) {
+                Ok(elements) => elements,
+                Err((bad_line, fault)) => {
+                    builder.reject(Location::Line(bad_line), fault)?;
+                    continue;
+                }
+            };
+
+            // Checked last so `Permissive` only ever keeps records that are
+            // otherwise well-formed.
+            let mut checksum_ok = true;
+            for (no, l) in [(line_1_no, line_1), (line_no, line)] {
+                if let Err(fault) = validate_checksum(l) {
+                    builder.report(Location::Line(no), fault)?;
+                    checksum_ok = false;
+                }
+            }
+            if !checksum_ok {
+                builder.catalog.summary.checksum_failures += 1;
+                if strictness != Strictness::Permissive {
+                    builder.catalog.summary.skipped += 1;
+                    continue;
+                }
+            }
+
+            builder.push(TleEntry {
+                name,
+                elements,
+                satrec: SatRec::twoline2rv(line_1, line, "wgs84"),
+            });
+        } else if line.starts_with("1 ") {
+            if let Some((line_1_no, _)) = tle_line_1.replace((line_no, line)) {
+                builder.reject(Location::Line(line_1_no), TleFault::MissingLine2)?;
+            }
+        } else if let Some((line_1_no, _)) = tle_line_1.take() {
+            // A title line (or junk) where line 2 should have been.
+            builder.reject(Location::Line(line_1_no), TleFault::MissingLine2)?;
+            name = Some(parse_name_line(line));
+        } else if line.starts_with("2 ") {
+            builder.reject(
+                Location::Line(line_no),
+                TleFault::LineNumber { expected: 1 },
+            )?;
+        } else {
+            name = Some(parse_name_line(line));
+        }
+    }
+
+    if let Some((line_1_no, _)) = tle_line_1 {
+        builder.reject(Location::Line(line_1_no), TleFault::MissingLine2)?;
+    }
+
+    Ok(builder.finish())
+}
+
+/// Space-Track 3LE files prefix the title line with `0 `; CelesTrak does not.
+fn parse_name_line(line: &str) -> String {
+    let name = line.strip_prefix("0 ").unwrap_or(line);
+    name.trim().to_string()
+}
+
+/// Check the layout of both element lines and parse their fields. On failure
+/// returns the line number the fault belongs to. Checksums are not checked.
+fn parse_record(
+    line_1_no: usize,
+    line_1: &str,
+    line_2_no: usize,
+    line_2: &str,
+) -> Result<MeanElements, (usize, TleFault)> {
+    validate_layout(line_1, 1).map_err(|f| (line_1_no, f))?;
+    validate_layout(line_2, 2).map_err(|f| (line_2_no, f))?;
+
+    let catalog_1 = parse_catalog_number(line_1).map_err(|f| (line_1_no, f))?;
+    let catalog_2 = parse_catalog_number(line_2).map_err(|f| (line_2_no, f))?;
+    if catalog_1 != catalog_2 {
+        return Err((
+            line_2_no,
+            TleFault::CatalogMismatch {
+                line1: catalog_1,
+                line2: catalog_2,
+            },
+        ));
+    }
+
+    let l1 = |f| (line_1_no, f);
+    let l2 = |f| (line_2_no, f);
+    let eccentricity = format!("0.{}", line_2[26..33].trim());
+    Ok(MeanElements {
+        catalog_number: catalog_1,
+        object_id: parse_designator(&line_1[9..17]),
+        classification: line_1[7..8].chars().next().unwrap_or('U'),
+        epoch: parse_epoch(&line_1[18..32]).map_err(l1)?,
+        mean_motion_dot: parse_field(&line_1[33..43], "mean motion dot").map_err(l1)?,
+        mean_motion_ddot: parse_exponent_field(&line_1[44..52], "mean motion ddot").map_err(l1)?,
+        bstar: parse_exponent_field(&line_1[53..61], "B*").map_err(l1)?,
+        element_set_no: parse_field(&line_1[64..68], "element set number").map_err(l1)?,
+        inclination: parse_field(&line_2[8..16], "inclination").map_err(l2)?,
+        raan: parse_field(&line_2[17..25], "right ascension").map_err(l2)?,
+        eccentricity: parse_field(&eccentricity, "eccentricity").map_err(l2)?,
+        arg_of_pericenter: parse_field(&line_2[34..42], "argument of perigee").map_err(l2)?,
+        mean_anomaly: parse_field(&line_2[43..51], "mean anomaly").map_err(l2)?,
+        mean_motion: parse_field(&line_2[52..63], "mean motion").map_err(l2)?,
+        rev_at_epoch: parse_field(&line_2[63..68], "revolution number").map_err(l2)?,
+    })
+}
+
+fn validate_layout(line: &str, line_number: u8) -> Result<(), TleFault> {
+    let expected_prefix = if line_number == 1 { "1 " } else { "2 " };
+    if !line.starts_with(expected_prefix) {
+        return Err(TleFault::LineNumber {
+            expected: line_number,
+        });
+    }
+    if !line.is_ascii() || line.len() != TLE_LINE_LEN {
+        return Err(TleFault::LineLength {
+            found: line.chars().count(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_checksum(line: &str) -> Result<(), TleFault> {
+    let expected = tle_checksum(line);
+    let found = line[TLE_LINE_LEN - 1..].chars().next().unwrap_or(' ');
+    if found.to_digit(10) != Some(expected) {
+        return Err(TleFault::Checksum { expected, found });
+    }
+    Ok(())
+}
+
+/// Columns 3-7 of either line. Supports Alpha-5, where a leading letter
+/// (skipping I and O) stands for 10..=33 ten-thousands.
+fn parse_catalog_number(line: &str) -> Result<u32, TleFault> {
+    let field = &line[2..7];
+    let invalid = || TleFault::Field {
+        name: "catalog number",
+        value: field.to_string(),
+    };
+
+    let mut chars = field.chars();
+    let first = chars.next().ok_or_else(invalid)?;
+    let high = match first {
+        ' ' => 0,
+        '0'..='9' => first as u32 - '0' as u32,
+        'A'..='H' => first as u32 - 'A' as u32 + 10,
+        'J'..='N' => first as u32 - 'J' as u32 + 18,
+        'P'..='Z' => first as u32 - 'P' as u32 + 23,
+        _ => return Err(invalid()),
+    };
+    let low = chars
+        .as_str()
+        .trim_start()
+        .parse::<u32>()
+        .map_err(|_| invalid())?;
+    Ok(high * 10_000 + low)
+}
+
+/// Parse a fixed-width field, accepting the TLE habit of dropping the leading
+/// zero (`-.00001234`).
+fn parse_field<T: std::str::FromStr>(field: &str, name: &'static str) -> Result<T, TleFault> {
+    let trimmed = field.trim();
+    let normalized = if let Some(rest) = trimmed.strip_prefix("-.") {
+        format!("-0.{}", rest)
+    } else if let Some(rest) = trimmed.strip_prefix('.') {
+        format!("0.{}", rest)
+    } else {
+        trimmed.to_string()
+    };
+    normalized.parse::<T>().map_err(|_| TleFault::Field {
+        name,
+        value: field.to_string(),
+    })
+}
+
+/// Assumed-decimal exponent notation: ` 29318-3` -> `0.29318e-3`.
+pub(crate) fn parse_exponent_field(field: &str, name: &'static str) -> Result<f64, TleFault> {
+    let invalid = || TleFault::Field {
+        name,
+        value: field.to_string(),
+    };
+    let trimmed = field.trim();
+    let (sign, rest) = match trimmed.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    let split = rest.rfind(['-', '+']).ok_or_else(invalid)?;
+    let mantissa = format!("0.{}", &rest[..split])
+        .parse::<f64>()
+        .map_err(|_| invalid())?;
+    let exponent = rest[split..].parse::<i32>().map_err(|_| invalid())?;
+    Ok(sign * mantissa * 10f64.powi(exponent))
+}
+
+/// `YYDDD.DDDDDDDD`, with two-digit years 57-99 in the 1900s.
+fn parse_epoch(field: &str) -> Result<DateTime<Utc>, TleFault> {
+    let invalid = || TleFault::Field {
+        name: "epoch",
+        value: field.to_string(),
+    };
+    let year = field[..2].trim().parse::<i32>().map_err(|_| invalid())?;
+    let year = if year < 57 { 2000 + year } else { 1900 + year };
+    let day = field[2..].trim().parse::<f64>().map_err(|_| invalid())?;
+
+    let jan_1 = NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(invalid)?;
+    let offset = Duration::microseconds(((day - 1.0) * 86_400e6).round() as i64);
+    Ok(jan_1.and_hms_opt(0, 0, 0).ok_or_else(invalid)?.and_utc() + offset)
+}
+
+/// `98067A  ` -> `1998-067A`.
+fn parse_designator(field: &str) -> String {
+    let field = field.trim();
+    match field.get(..2).and_then(|yy| yy.parse::<u32>().ok()) {
+        Some(yy) if field.len() > 2 => {
+            let year = if yy < 57 { 2000 + yy } else { 1900 + yy };
+            format!("{}-{}", year, &field[2..])
+        }
+        _ => field.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISS_LINE_1: &str =
+        "1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927";
+    const ISS_LINE_2: &str =
+        "2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537";
+
+    /// The ISS record with line 1's checksum digit changed.
+    fn bad_checksum_catalog() -> String {
+        format!("ISS (ZARYA)\n{}8\n{}\n", &ISS_LINE_1[..68], ISS_LINE_2)
+    }
+
+    #[test]
+    fn parses_iss() {
+        let text = format!("ISS (ZARYA)\n{}\n{}\n", ISS_LINE_1, ISS_LINE_2);
+        let catalog = parse_tles(Path::new("iss.tle"), &text, Strictness::Strict).unwrap();
+        assert_eq!(catalog.summary.loaded, 1);
+        let entry = &catalog.entries[0];
+        assert_eq!(entry.name.as_deref(), Some("ISS (ZARYA)"));
+
+        let elements = &entry.elements;
+        assert_eq!(elements.catalog_number, 25544);
+        assert_eq!(elements.object_id, "1998-067A");
+        assert_eq!(elements.classification, 'U');
+        assert_eq!(elements.mean_motion_dot, -0.00002182);
+        assert_eq!(elements.bstar, -0.11606e-4);
+        assert_eq!(elements.element_set_no, 292);
+        assert_eq!(elements.inclination, 51.6416);
+        assert_eq!(elements.eccentricity, 0.0006703);
+        assert_eq!(elements.mean_motion, 15.72125391);
+        assert_eq!(elements.rev_at_epoch, 56353);
+    }
+
+    #[test]
+    fn validates_checksum() {
+        assert_eq!(validate_checksum(ISS_LINE_1), Ok(()));
+        assert_eq!(validate_checksum(ISS_LINE_2), Ok(()));
+        let bad = format!("{}8", &ISS_LINE_1[..68]);
+        assert_eq!(
+            validate_checksum(&bad),
+            Err(TleFault::Checksum {
+                expected: 7,
+                found: '8'
+            })
+        );
+    }
+
+    #[test]
+    fn bad_checksum_under_each_strictness() {
+        let path = Path::new("iss.tle");
+        let text = bad_checksum_catalog();
+
+        match parse_tles(path, &text, Strictness::Strict) {
+            Err(TleError::Malformed {
+                location: Location::Line(2),
+                fault: TleFault::Checksum { .. },
+                ..
+            }) => {}
+            other => panic!("expected a checksum error, got {:?}", other.map(|_| ())),
+        }
+
+        let lenient = parse_tles(path, &text, Strictness::Lenient).unwrap();
+        assert!(lenient.entries.is_empty());
+        assert_eq!(lenient.summary.skipped, 1);
+        assert_eq!(lenient.summary.checksum_failures, 1);
+        assert_eq!(lenient.summary.diagnostics.len(), 1);
+
+        let permissive = parse_tles(path, &text, Strictness::Permissive).unwrap();
+        assert_eq!(permissive.entries.len(), 1);
+        assert_eq!(permissive.summary.skipped, 0);
+        assert_eq!(permissive.summary.checksum_failures, 1);
+        assert_eq!(permissive.summary.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn permissive_still_skips_other_faults() {
+        // Bad checksum and a catalog number mismatch.
+        let line_2 = ISS_LINE_2.replacen("25544", "25545", 1);
+        let text = format!("{}8\n{}\n", &ISS_LINE_1[..68], line_2);
+        let catalog = parse_tles(Path::new("iss.tle"), &text, Strictness::Permissive).unwrap();
+        assert!(catalog.entries.is_empty());
+        assert_eq!(catalog.summary.skipped, 1);
+    }
+
+    #[test]
+    fn parses_alpha_5_catalog_numbers() {
+        let number = |field: &str| parse_catalog_number(&format!("1 {}U", field));
+        assert_eq!(number("25544"), Ok(25544));
+        assert_eq!(number(" 1234"), Ok(1234));
+        assert_eq!(number("A0000"), Ok(100_000));
+        assert_eq!(number("E8493"), Ok(148_493));
+        assert_eq!(number("H9999"), Ok(179_999));
+        assert_eq!(number("J0001"), Ok(180_001));
+        assert_eq!(number("N5000"), Ok(225_000));
+        assert_eq!(number("P0000"), Ok(230_000));
+        assert_eq!(number("Z9999"), Ok(339_999));
+        for bad in ["I0000", "O0000", "a0000", "2554x"] {
+            assert!(number(bad).is_err(), "{bad} accepted");
+        }
+    }
+
+    #[test]
+    fn parses_exponent_fields() {
+        let parse = |field| parse_exponent_field(field, "B*").unwrap();
+        assert_eq!(parse(" 29318-3"), 0.29318e-3);
+        assert_eq!(parse("-11606-4"), -0.11606e-4);
+        assert!((parse("+12345+1") - 1.2345).abs() < 1e-12);
+        assert_eq!(parse(" 00000-0"), 0.0);
+        assert_eq!(parse(" 00000+0"), 0.0);
+        assert!(parse_exponent_field(" 29318 3", "B*").is_err());
+        assert!(parse_exponent_field("        ", "B*").is_err());
+    }
+
+    #[test]
+    fn parses_epochs() {
+        let epoch = |field| parse_epoch(field).unwrap().to_rfc3339();
+        assert_eq!(epoch("08264.51782528"), "2008-09-20T12:25:40.104192+00:00");
+        assert_eq!(epoch("57001.00000000"), "1957-01-01T00:00:00+00:00");
+        assert_eq!(epoch("56366.50000000"), "2056-12-31T12:00:00+00:00");
+        assert!(parse_epoch("0x264.51782528").is_err());
+    }
 }
@@ -1,55 +1,420 @@
-use bevy::prelude::*;
-use std::f32::consts::{FRAC_PI_2, PI};
+use bevy::{input::InputSystems, prelude::*};
+mod benchmark;
 mod camera;
+mod cli;
+mod collision;
+mod coloring;
+mod conjunction;
 mod debris;
+mod earth;
+mod elements;
+mod filter;
+mod filtering;
+mod frames;
+mod ground_track;
+mod instancing;
 mod loader;
+mod omm;
+mod orbits;
+mod propagation;
+mod satcat;
+mod selection;
+mod sensors;
+mod stations;
+mod sun;
+mod synthetic;
+mod time_control;
+mod timeline;
+mod visibility;
 
-use crate::debris::{setup_debris_field, setup_simulation_time, update_debris_positions};
-use camera::{CameraSettings, orbit_camera, setup_camera, zoom_camera};
+use crate::debris::{setup_debris_field, setup_simulation_time, spawn_debris_from_catalog};
+use crate::loader::{TleCatalog, TleCatalogLoader};
+use benchmark::{FrameBenchmark, run_frame_benchmark, run_propagation_benchmark};
+use camera::{
+    CameraSettings, focus_camera, follow_display_frame, follow_focus, orbit_camera, setup_camera,
+    zoom_camera,
+};
+use clap::Parser;
+use cli::Cli;
+use coloring::{
+    apply_color_scheme, cycle_color_scheme, setup_color_legend, update_color_legend,
+    update_debris_materials,
+};
+use conjunction::{
+    Conjunctions, conjunction_input, jump_to_conjunction, poll_screening, run_screening,
+    setup_conjunction_panel, update_conjunction_panel,
+};
+use earth::{
+    Earth, EarthMaterial, NIGHT_LIGHTS_NITS, NightLights, NightLightsSource, rotate_earth,
+    toggle_display_frame, update_earth_rotation,
+};
+use filtering::{
+    FilterInput, apply_filter, edit_filter, list_matching, read_matching, setup_filter_bar,
+    update_filter_bar,
+};
+use ground_track::{draw_ground_tracks, toggle_ground_track, update_ground_tracks};
+use instancing::{DebrisCloudMaterial, setup_debris_cloud, write_debris_instances};
+use orbits::{draw_orbit_trails, pin_orbits, update_orbit_trails};
+use propagation::{
+    DebrisStates, propagate_debris, setup_propagation_counter, update_debris_positions,
+    update_propagation_counter,
+};
+use satcat::{Satcat, SatcatLoader, attach_satellite_metadata, load_satcat};
+use selection::{
+    Selection, clear_selection, draw_selection_highlight, pick_debris, setup_selection_ui,
+    update_hover_tooltip, update_info_panel,
+};
+use sensors::{place_sensor_volumes, run_coverage, setup_sensors, update_sensor_panel};
+use stations::{
+    SelectedPasses, draw_ground_stations, predict_selected_passes, run_pass_prediction,
+    setup_pass_panel, update_pass_panel,
+};
+use sun::{Sun, orient_sunlight, setup_sunlight, update_illumination, update_sun};
+use synthetic::add_synthetic_population;
+use time_control::{advance_simulation_time, setup_clock, time_control_input, update_clock};
+use timeline::{drag_timeline, setup_timeline, update_timeline};
+use visibility::{NIGHT_SEARCH_DAYS, run_tonight, setup_sky_panel, update_sky_panel};
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
-        .init_resource::<CameraSettings>()
-        .add_systems(
-            Startup,
+    let cli = Cli::parse();
+    if cli.list {
+        match list_matching(&cli.catalogs, cli.satcat.as_deref(), cli.filter.as_ref()) {
+            Ok(matched) => eprintln!("{} objects", matched),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(count) = cli.bench_propagation {
+        run_propagation_benchmark(count);
+        return;
+    }
+    if cli.screen {
+        let start = cli.epoch.unwrap_or_else(chrono::Utc::now);
+        match run_screening(&cli.catalogs, start, &cli.screening_settings()) {
+            Ok(found) => eprintln!("{} conjunctions", found),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if cli.passes {
+        if cli.stations.is_empty() {
+            eprintln!("--passes needs at least one --station");
+            std::process::exit(1);
+        }
+        let start = cli.epoch.unwrap_or_else(chrono::Utc::now);
+        let predicted = read_matching(&cli.catalogs, cli.satcat.as_deref(), cli.filter.as_ref())
+            .and_then(|entries| {
+                run_pass_prediction(
+                    entries,
+                    &cli.ground_stations(),
+                    start,
+                    cli.pass_hours / 24.0,
+                    cli.earth_rotation().polar_motion,
+                    cli.pass_track,
+                )
+            });
+        match predicted {
+            Ok(passes) => eprintln!("{} passes", passes),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if cli.tonight {
+        if cli.stations.is_empty() {
+            eprintln!("--tonight needs at least one --station");
+            std::process::exit(1);
+        }
+        let start = cli.epoch.unwrap_or_else(chrono::Utc::now);
+        let stations = cli.ground_stations();
+        let predicted = read_matching(&cli.catalogs, cli.satcat.as_deref(), cli.filter.as_ref())
+            .and_then(|entries| {
+                run_tonight(
+                    entries,
+                    &stations,
+                    &cli.optical_settings(),
+                    start,
+                    cli.earth_rotation().polar_motion,
+                )
+            });
+        match predicted {
+            Ok(tonight) => {
+                let time = |t: chrono::DateTime<chrono::Utc>| t.format("%Y-%m-%dT%H:%M:%SZ");
+                for (station, night) in stations.0.iter().zip(&tonight.nights) {
+                    match night {
+                        Some((dusk, dawn)) => eprintln!(
+                            "{}: dark from {} to {}",
+                            station.name,
+                            time(*dusk),
+                            time(*dawn)
+                        ),
+                        None => eprintln!(
+                            "{}: no darkness in the next {} h",
+                            station.name,
+                            NIGHT_SEARCH_DAYS * 24.0
+                        ),
+                    }
+                }
+                eprintln!("{} visible passes", tonight.passes);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if cli.coverage {
+        if cli.sensors.is_empty() {
+            eprintln!("--coverage needs at least one --sensor");
+            std::process::exit(1);
+        }
+        let start = cli.epoch.unwrap_or_else(chrono::Utc::now);
+        let covered = read_matching(&cli.catalogs, cli.satcat.as_deref(), cli.filter.as_ref())
+            .and_then(|entries| {
+                run_coverage(
+                    entries,
+                    &cli.sensors(),
+                    start,
+                    cli.coverage_hours / 24.0,
+                    cli.coverage_step,
+                    cli.earth_rotation().polar_motion,
+                )
+            });
+        match covered {
+            Ok(unique) => eprintln!("{} objects detected", unique),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let mut app = App::new();
+    // Catalog asset sources have to exist before the AssetPlugin starts.
+    let catalog_sources = cli.register_catalog_sources(&mut app);
+    let satcat_source = cli.register_satcat_source(&mut app);
+    let night_lights_source = cli.register_night_lights_source(&mut app);
+
+    app.add_plugins((
+        DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(cli.window()),
+            ..default()
+        }),
+        MaterialPlugin::<DebrisCloudMaterial> {
+            prepass_enabled: false,
+            shadows_enabled: false,
+            ..default()
+        },
+        MaterialPlugin::<EarthMaterial>::default(),
+    ))
+    .insert_resource(catalog_sources)
+    .insert_resource(satcat_source)
+    .insert_resource(night_lights_source)
+    .insert_resource(cli.simulation_config())
+    .insert_resource(cli.camera_start())
+    .insert_resource(cli.timeline_settings())
+    .insert_resource(cli.orbit_trail_settings())
+    .insert_resource(cli.ground_track_settings())
+    .insert_resource(cli.earth_rotation())
+    .insert_resource(cli.frame)
+    .insert_resource(cli.render)
+    .insert_resource(cli.color_by)
+    .insert_resource(cli.active_filter())
+    .insert_resource(cli.screening_settings())
+    .insert_resource(cli.ground_stations())
+    .insert_resource(cli.optical_settings())
+    .insert_resource(cli.sensors())
+    .insert_resource(cli.synthetic_population())
+    .insert_resource(cli.propagation_settings())
+    .init_resource::<DebrisStates>()
+    .init_resource::<Sun>()
+    .init_asset::<TleCatalog>()
+    .init_asset_loader::<TleCatalogLoader>()
+    .init_asset::<Satcat>()
+    .init_asset_loader::<SatcatLoader>()
+    .init_resource::<CameraSettings>()
+    .init_resource::<Selection>()
+    .init_resource::<FilterInput>()
+    .init_resource::<Conjunctions>()
+    .init_resource::<SelectedPasses>()
+    .add_systems(
+        Startup,
+        (
+            setup_scene,
+            setup_sunlight,
+            show_instructions,
+            setup_clock,
+            setup_timeline,
+            setup_selection_ui,
+            setup_propagation_counter,
+            setup_color_legend,
+            setup_filter_bar,
+            setup_conjunction_panel,
+            setup_pass_panel,
+            setup_sky_panel,
+            setup_sensors,
+            setup_camera,
+            (setup_debris_field, add_synthetic_population).chain(),
+            setup_debris_cloud,
+            setup_simulation_time,
+            load_satcat,
+        ),
+    )
+    .add_systems(PreUpdate, edit_filter.after(InputSystems))
+    .add_systems(
+        Update,
+        (
+            orbit_camera,
+            zoom_camera,
+            (focus_camera, follow_focus)
+                .chain()
+                .after(orbit_camera)
+                .after(zoom_camera)
+                .after(pick_debris)
+                .after(update_debris_positions)
+                .after(follow_display_frame),
+            (spawn_debris_from_catalog, attach_satellite_metadata).chain(),
+            (
+                time_control_input,
+                advance_simulation_time,
+                drag_timeline,
+                (update_earth_rotation, update_sun),
+                toggle_display_frame,
+                (
+                    (
+                        propagate_debris,
+                        update_debris_positions,
+                        write_debris_instances,
+                    )
+                        .chain(),
+                    update_illumination
+                        .after(propagate_debris)
+                        .before(apply_filter)
+                        .before(apply_color_scheme),
+                    apply_filter
+                        .after(attach_satellite_metadata)
+                        .before(write_debris_instances),
+                    (
+                        cycle_color_scheme,
+                        apply_color_scheme,
+                        (update_debris_materials, update_color_legend),
+                    )
+                        .chain()
+                        .after(attach_satellite_metadata)
+                        .before(write_debris_instances),
+                    rotate_earth,
+                    orient_sunlight,
+                    follow_display_frame,
+                ),
+                (
+                    update_clock,
+                    update_timeline,
+                    update_propagation_counter,
+                    update_filter_bar,
+                ),
+            )
+                .chain(),
             (
-                setup_scene,
-                show_instructions,
-                setup_camera,
-                setup_debris_field,
-                setup_simulation_time,
-            ),
-        )
-        .add_systems(Update, (orbit_camera, zoom_camera, update_debris_positions))
-        .run();
+                (clear_selection, pick_debris),
+                pin_orbits,
+                (
+                    draw_selection_highlight,
+                    update_info_panel,
+                    update_hover_tooltip,
+                ),
+            )
+                .chain(),
+            (update_orbit_trails, draw_orbit_trails)
+                .chain()
+                .after(toggle_display_frame)
+                .after(pin_orbits),
+            (
+                toggle_ground_track,
+                update_ground_tracks,
+                draw_ground_tracks,
+            )
+                .chain()
+                .after(toggle_display_frame)
+                .after(pin_orbits),
+            draw_ground_stations.after(update_debris_positions),
+            update_sky_panel
+                .after(update_debris_positions)
+                .after(update_sun)
+                .after(apply_filter)
+                .after(toggle_display_frame),
+            (place_sensor_volumes, update_sensor_panel)
+                .after(update_debris_positions)
+                .after(toggle_display_frame),
+            (predict_selected_passes, update_pass_panel)
+                .chain()
+                .after(pick_debris)
+                .after(advance_simulation_time),
+            (
+                conjunction_input,
+                poll_screening,
+                update_conjunction_panel,
+                jump_to_conjunction,
+            )
+                .chain()
+                .before(advance_simulation_time),
+            run_frame_benchmark.run_if(resource_exists::<FrameBenchmark>),
+        ),
+    );
+
+    if let Some(benchmark) = cli.frame_benchmark() {
+        app.insert_resource(benchmark);
+    }
+    app.run();
 }
 
 fn setup_scene(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut materials: ResMut<Assets<EarthMaterial>>,
     asset_server: Res<AssetServer>,
+    night_lights: Res<NightLightsSource>,
 ) {
     let earth_mesh = meshes.add(Sphere::new(1.0).mesh().uv(128, 64));
     let earth_texture: Handle<Image> = asset_server.load("earth.jpg");
+    let night_texture: Option<Handle<Image>> =
+        night_lights.0.as_ref().map(|path| asset_server.load(path));
 
-    let earth_material = materials.add(StandardMaterial {
-        base_color_texture: Some(earth_texture),
-        unlit: true,
-        ..default()
+    // Lit by the Sun (see `sun::orient_sunlight`): a matte surface, so the
+    // day side shows the texture rather than a specular highlight.
+    let earth_material = materials.add(EarthMaterial {
+        base: StandardMaterial {
+            base_color_texture: Some(earth_texture),
+            perceptual_roughness: 1.0,
+            reflectance: 0.1,
+            ..default()
+        },
+        extension: NightLights {
+            brightness: if night_texture.is_some() {
+                NIGHT_LIGHTS_NITS
+            } else {
+                0.0
+            },
+            texture: night_texture,
+        },
     });
 
+    // Oriented every frame by `rotate_earth`.
     commands.spawn((
         Name::new("Earth"),
+        Earth,
         Mesh3d(earth_mesh),
         MeshMaterial3d(earth_material),
-        Transform::from_rotation(Quat::from_euler(
-            EulerRot::XYZ,
-            -FRAC_PI_2,
-            0.0,
-            2.0 * PI - FRAC_PI_2,
-        )),
+        Transform::default(),
         GlobalTransform::default(),
     ));
 }
@@ -59,7 +424,19 @@ fn show_instructions(mut commands: Commands) {
         Name::new("Instructions"),
         Text::new(
             "Left mouse: drag to orbit\n\
-             Scroll wheel: zoom",
+             Left click: select object (Esc to clear)\n\
+             P: pin / unpin orbit (Shift+P: clear pins)\n\
+             T: follow selected object (Shift+T: back to Earth)\n\
+             G: toggle ground track\n\
+             F: inertial / Earth-fixed frame\n\
+             Scroll wheel: zoom\n\
+             Space: play / pause\n\
+             , / .: slower / faster\n\
+             R: reverse time\n\
+             Left / Right: step one frame\n\
+             N: jump to now\n\
+             Home: jump to start epoch\n\
+             Timeline: drag to scrub",
         ),
         Node {
             position_type: PositionType::Absolute,
@@ -0,0 +1,583 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::elements::MeanElements;
+use crate::loader::{
+    CatalogBuilder, Location, Strictness, TleCatalog, TleEntry, TleError, TleFault,
+};
+
+/// Catalog file formats understood by the loader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogFormat {
+    Tle,
+    OmmXml,
+    OmmKvn,
+    GpJson,
+    GpCsv,
+}
+
+impl CatalogFormat {
+    /// Pick a format from the file extension, falling back to sniffing the
+    /// content for extensions that don't say (`.txt`, `.omm`, none).
+    pub fn detect(path: &Path, text: &str) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("tle" | "3le") => CatalogFormat::Tle,
+            Some("xml") => CatalogFormat::OmmXml,
+            Some("kvn") => CatalogFormat::OmmKvn,
+            Some("json") => CatalogFormat::GpJson,
+            Some("csv") => CatalogFormat::GpCsv,
+            _ => Self::sniff(text),
+        }
+    }
+
+    fn sniff(text: &str) -> Self {
+        let trimmed = text.trim_start();
+        let first_line = trimmed.lines().next().unwrap_or("");
+        if trimmed.starts_with('<') {
+            CatalogFormat::OmmXml
+        } else if trimmed.starts_with('[') || trimmed.starts_with('{') {
+            CatalogFormat::GpJson
+        } else if first_line.starts_with("CCSDS_OMM_VERS") {
+            CatalogFormat::OmmKvn
+        } else if first_line.contains(',') && first_line.contains("OBJECT_NAME") {
+            CatalogFormat::GpCsv
+        } else {
+            CatalogFormat::Tle
+        }
+    }
+}
+
+/// OMM keyword -> value for a single record. OMM XML / KVN and the CelesTrak
+/// GP JSON / CSV exports all use the same keywords, so each parser only has to
+/// build one of these and the mapping to [`MeanElements`] is shared.
+type Fields = HashMap<String, String>;
+
+/// Parse a CCSDS OMM XML document. Handles both a bare `<omm>` and an `<ndm>`
+/// wrapping several of them.
+pub fn parse_omm_xml(
+    path: &Path,
+    text: &str,
+    strictness: Strictness,
+) -> Result<TleCatalog, TleError> {
+    let mut builder = CatalogBuilder::new(path, strictness);
+
+    let doc = match roxmltree::Document::parse(text) {
+        Ok(doc) => doc,
+        Err(e) => {
+            let line = e.pos().row as usize;
+            builder.reject(
+                Location::Line(line),
+                TleFault::Syntax {
+                    message: e.to_string(),
+                },
+            )?;
+            return Ok(builder.finish());
+        }
+    };
+
+    for omm in doc
+        .descendants()
+        .filter(|n| n.has_tag_name("omm") || n.has_tag_name("OMM"))
+    {
+        let line = doc.text_pos_at(omm.range().start).row as usize;
+        // Every leaf element is a keyword; nesting (metadata / meanElements /
+        // tleParameters) carries no extra meaning for us.
+        let fields = omm
+            .descendants()
+            .filter(|n| n.is_element() && !n.children().any(|c| c.is_element()))
+            .filter_map(|n| {
+                Some((
+                    n.tag_name().name().to_string(),
+                    n.text()?.trim().to_string(),
+                ))
+            })
+            .collect::<Fields>();
+        push_record(&mut builder, Location::Line(line), &fields)?;
+    }
+
+    Ok(builder.finish())
+}
+
+/// Parse one or more CCSDS OMM KVN messages. Each message starts with a
+/// `CCSDS_OMM_VERS` line.
+pub fn parse_omm_kvn(
+    path: &Path,
+    text: &str,
+    strictness: Strictness,
+) -> Result<TleCatalog, TleError> {
+    let mut builder = CatalogBuilder::new(path, strictness);
+
+    let mut fields = Fields::new();
+    let mut start_line = 1;
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("COMMENT") {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            builder.report(
+                Location::Line(idx + 1),
+                TleFault::Syntax {
+                    message: format!("expected `KEY = VALUE`, found `{}`", line),
+                },
+            )?;
+            continue;
+        };
+        let key = key.trim();
+        if key == "CCSDS_OMM_VERS" && !fields.is_empty() {
+            push_record(&mut builder, Location::Line(start_line), &fields)?;
+            fields.clear();
+        }
+        if fields.is_empty() {
+            start_line = idx + 1;
+        }
+        // Values may carry units: `MEAN_MOTION = 15.49 [rev/day]`.
+        let value = value.split('[').next().unwrap_or("").trim();
+        fields.insert(key.to_string(), value.to_string());
+    }
+    if !fields.is_empty() {
+        push_record(&mut builder, Location::Line(start_line), &fields)?;
+    }
+
+    Ok(builder.finish())
+}
+
+/// Parse a CelesTrak GP JSON export (an array of OMM keyword objects).
+pub fn parse_gp_json(
+    path: &Path,
+    text: &str,
+    strictness: Strictness,
+) -> Result<TleCatalog, TleError> {
+    let mut builder = CatalogBuilder::new(path, strictness);
+
+    let records = match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(serde_json::Value::Array(records)) => records,
+        Ok(record @ serde_json::Value::Object(_)) => vec![record],
+        Ok(_) => {
+            builder.reject(
+                Location::Line(1),
+                TleFault::Syntax {
+                    message: "expected an array of GP records".to_string(),
+                },
+            )?;
+            return Ok(builder.finish());
+        }
+        Err(e) => {
+            builder.reject(
+                Location::Line(e.line()),
+                TleFault::Syntax {
+                    message: e.to_string(),
+                },
+            )?;
+            return Ok(builder.finish());
+        }
+    };
+
+    for (index, record) in records.iter().enumerate() {
+        let Some(object) = record.as_object() else {
+            builder.reject(
+                Location::Record(index),
+                TleFault::Syntax {
+                    message: "expected a JSON object".to_string(),
+                },
+            )?;
+            continue;
+        };
+        let fields = object
+            .iter()
+            .filter_map(|(key, value)| {
+                let value = match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    serde_json::Value::Number(n) => n.to_string(),
+                    _ => return None,
+                };
+                Some((key.clone(), value))
+            })
+            .collect::<Fields>();
+        push_record(&mut builder, Location::Record(index), &fields)?;
+    }
+
+    Ok(builder.finish())
+}
+
+/// Parse a CelesTrak GP CSV export (header row of OMM keywords).
+pub fn parse_gp_csv(
+    path: &Path,
+    text: &str,
+    strictness: Strictness,
+) -> Result<TleCatalog, TleError> {
+    let mut builder = CatalogBuilder::new(path, strictness);
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            builder.reject(
+                Location::Line(1),
+                TleFault::Syntax {
+                    message: e.to_string(),
+                },
+            )?;
+            return Ok(builder.finish());
+        }
+    };
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|p| p.line() as usize).unwrap_or(0);
+                builder.reject(
+                    Location::Line(line),
+                    TleFault::Syntax {
+                        message: e.to_string(),
+                    },
+                )?;
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+        let fields = headers
+            .iter()
+            .zip(record.iter())
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<Fields>();
+        push_record(&mut builder, Location::Line(line), &fields)?;
+    }
+
+    Ok(builder.finish())
+}
+
+fn push_record(
+    builder: &mut CatalogBuilder<'_>,
+    location: Location,
+    fields: &Fields,
+) -> Result<(), TleError> {
+    match elements_from_fields(fields) {
+        Ok(elements) => {
+            builder.push(TleEntry {
+                name: fields.get("OBJECT_NAME").cloned(),
+                satrec: elements.to_satrec(),
+                elements,
+            });
+            Ok(())
+        }
+        Err(fault) => builder.reject(location, fault),
+    }
+}
+
+fn elements_from_fields(fields: &Fields) -> Result<MeanElements, TleFault> {
+    // Only classic SGP4 mean elements can be fed to SatRec. SGP4-XP ones are
+    // fitted for a different propagator and would give wrong positions.
+    if let Some(theory) = fields.get("MEAN_ELEMENT_THEORY")
+        && !matches!(theory.as_str(), "SGP4" | "SGP/SGP4")
+    {
+        return Err(TleFault::Field {
+            name: "MEAN_ELEMENT_THEORY",
+            value: theory.clone(),
+        });
+    }
+
+    Ok(MeanElements {
+        catalog_number: number(fields, "NORAD_CAT_ID")?,
+        object_id: fields.get("OBJECT_ID").cloned().unwrap_or_default(),
+        classification: fields
+            .get("CLASSIFICATION_TYPE")
+            .and_then(|c| c.chars().next())
+            .unwrap_or('U'),
+        epoch: epoch(fields)?,
+        mean_motion: number(fields, "MEAN_MOTION")?,
+        eccentricity: number(fields, "ECCENTRICITY")?,
+        inclination: number(fields, "INCLINATION")?,
+        raan: number(fields, "RA_OF_ASC_NODE")?,
+        arg_of_pericenter: number(fields, "ARG_OF_PERICENTER")?,
+        mean_anomaly: number(fields, "MEAN_ANOMALY")?,
+        bstar: optional_number(fields, "BSTAR")?.unwrap_or(0.0),
+        mean_motion_dot: optional_number(fields, "MEAN_MOTION_DOT")?.unwrap_or(0.0),
+        mean_motion_ddot: optional_number(fields, "MEAN_MOTION_DDOT")?.unwrap_or(0.0),
+        element_set_no: optional_number(fields, "ELEMENT_SET_NO")?.unwrap_or(999),
+        rev_at_epoch: optional_number(fields, "REV_AT_EPOCH")?.unwrap_or(0),
+    })
+}
+
+fn number<T: std::str::FromStr>(fields: &Fields, name: &'static str) -> Result<T, TleFault> {
+    optional_number(fields, name)?.ok_or(TleFault::MissingField { name })
+}
+
+fn optional_number<T: std::str::FromStr>(
+    fields: &Fields,
+    name: &'static str,
+) -> Result<Option<T>, TleFault> {
+    let Some(value) = fields.get(name) else {
+        return Ok(None);
+    };
+    value.parse::<T>().map(Some).map_err(|_| TleFault::Field {
+        name,
+        value: value.clone(),
+    })
+}
+
+/// OMM epochs are UTC, either calendar (`2025-12-04T13:02:29.700384`) or
+/// day-of-year (`2025-338T13:02:29.7`), with or without a trailing `Z`.
+fn epoch(fields: &Fields) -> Result<DateTime<Utc>, TleFault> {
+    let value = fields
+        .get("EPOCH")
+        .ok_or(TleFault::MissingField { name: "EPOCH" })?;
+    let trimmed = value.trim_end_matches('Z');
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%jT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(trimmed, format).ok())
+        .map(|naive| naive.and_utc())
+        .ok_or_else(|| TleFault::Field {
+            name: "EPOCH",
+            value: value.clone(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::parse_tles;
+
+    const ISS_TLE: &str = "ISS (ZARYA)
+1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537
+";
+
+    const ISS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ndm>
+  <omm id="CCSDS_OMM_VERS" version="2.0">
+    <header><CREATION_DATE/><ORIGINATOR/></header>
+    <body><segment>
+      <metadata>
+        <OBJECT_NAME>ISS (ZARYA)</OBJECT_NAME>
+        <OBJECT_ID>1998-067A</OBJECT_ID>
+        <CENTER_NAME>EARTH</CENTER_NAME>
+        <REF_FRAME>TEME</REF_FRAME>
+        <TIME_SYSTEM>UTC</TIME_SYSTEM>
+        <MEAN_ELEMENT_THEORY>SGP4</MEAN_ELEMENT_THEORY>
+      </metadata>
+      <data>
+        <meanElements>
+          <EPOCH>2008-09-20T12:25:40.104192</EPOCH>
+          <MEAN_MOTION>15.72125391</MEAN_MOTION>
+          <ECCENTRICITY>.0006703</ECCENTRICITY>
+          <INCLINATION>51.6416</INCLINATION>
+          <RA_OF_ASC_NODE>247.4627</RA_OF_ASC_NODE>
+          <ARG_OF_PERICENTER>130.5360</ARG_OF_PERICENTER>
+          <MEAN_ANOMALY>325.0288</MEAN_ANOMALY>
+        </meanElements>
+        <tleParameters>
+          <EPHEMERIS_TYPE>0</EPHEMERIS_TYPE>
+          <CLASSIFICATION_TYPE>U</CLASSIFICATION_TYPE>
+          <NORAD_CAT_ID>25544</NORAD_CAT_ID>
+          <ELEMENT_SET_NO>292</ELEMENT_SET_NO>
+          <REV_AT_EPOCH>56353</REV_AT_EPOCH>
+          <BSTAR>-.11606E-4</BSTAR>
+          <MEAN_MOTION_DOT>-.00002182</MEAN_MOTION_DOT>
+          <MEAN_MOTION_DDOT>0</MEAN_MOTION_DDOT>
+        </tleParameters>
+      </data>
+    </segment></body>
+  </omm>
+</ndm>
+"#;
+
+    const ISS_KVN: &str = "CCSDS_OMM_VERS = 2.0
+COMMENT Converted from the Vallado ISS example
+OBJECT_NAME = ISS (ZARYA)
+OBJECT_ID = 1998-067A
+CENTER_NAME = EARTH
+REF_FRAME = TEME
+TIME_SYSTEM = UTC
+MEAN_ELEMENT_THEORY = SGP4
+EPOCH = 2008-264T12:25:40.104192Z
+MEAN_MOTION = 15.72125391 [rev/day]
+ECCENTRICITY = 0.0006703
+INCLINATION = 51.6416 [deg]
+RA_OF_ASC_NODE = 247.4627 [deg]
+ARG_OF_PERICENTER = 130.5360 [deg]
+MEAN_ANOMALY = 325.0288 [deg]
+EPHEMERIS_TYPE = 0
+CLASSIFICATION_TYPE = U
+NORAD_CAT_ID = 25544
+ELEMENT_SET_NO = 292
+REV_AT_EPOCH = 56353
+BSTAR = -0.11606E-4 [1/ER]
+MEAN_MOTION_DOT = -0.00002182 [rev/day**2]
+MEAN_MOTION_DDOT = 0 [rev/day**3]
+";
+
+    const ISS_JSON: &str = r#"[{
+        "OBJECT_NAME": "ISS (ZARYA)",
+        "OBJECT_ID": "1998-067A",
+        "EPOCH": "2008-09-20T12:25:40.104192",
+        "MEAN_MOTION": 15.72125391,
+        "ECCENTRICITY": 0.0006703,
+        "INCLINATION": 51.6416,
+        "RA_OF_ASC_NODE": 247.4627,
+        "ARG_OF_PERICENTER": 130.536,
+        "MEAN_ANOMALY": 325.0288,
+        "EPHEMERIS_TYPE": 0,
+        "CLASSIFICATION_TYPE": "U",
+        "NORAD_CAT_ID": 25544,
+        "ELEMENT_SET_NO": 292,
+        "REV_AT_EPOCH": 56353,
+        "BSTAR": -1.1606e-5,
+        "MEAN_MOTION_DOT": -2.182e-5,
+        "MEAN_MOTION_DDOT": 0
+    }]"#;
+
+    const ISS_CSV: &str = "OBJECT_NAME,OBJECT_ID,EPOCH,MEAN_MOTION,ECCENTRICITY,INCLINATION,RA_OF_ASC_NODE,ARG_OF_PERICENTER,MEAN_ANOMALY,EPHEMERIS_TYPE,CLASSIFICATION_TYPE,NORAD_CAT_ID,ELEMENT_SET_NO,REV_AT_EPOCH,BSTAR,MEAN_MOTION_DOT,MEAN_MOTION_DDOT
+ISS (ZARYA),1998-067A,2008-09-20T12:25:40.104192,15.72125391,.0006703,51.6416,247.4627,130.5360,325.0288,0,U,25544,292,56353,-.11606e-4,-.00002182,0
+";
+
+    fn only_entry(catalog: TleCatalog) -> TleEntry {
+        assert_eq!(catalog.summary.loaded, 1);
+        assert!(catalog.summary.diagnostics.is_empty());
+        catalog.entries.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn iss_is_the_same_in_every_format() {
+        let strict = Strictness::Strict;
+        let tle = only_entry(parse_tles(Path::new("iss.tle"), ISS_TLE, strict).unwrap());
+        let others = [
+            ("xml", parse_omm_xml(Path::new("iss.xml"), ISS_XML, strict)),
+            ("kvn", parse_omm_kvn(Path::new("iss.kvn"), ISS_KVN, strict)),
+            (
+                "json",
+                parse_gp_json(Path::new("iss.json"), ISS_JSON, strict),
+            ),
+            ("csv", parse_gp_csv(Path::new("iss.csv"), ISS_CSV, strict)),
+        ];
+        for (format, catalog) in others {
+            let entry = only_entry(catalog.unwrap());
+            assert_eq!(entry.name, tle.name, "{format}");
+            assert_eq!(entry.elements, tle.elements, "{format}");
+        }
+    }
+
+    #[test]
+    fn skips_records_without_sgp4_elements() {
+        let json = ISS_JSON.replace(r#""MEAN_MOTION": 15.72125391,"#, "");
+        let catalog = parse_gp_json(Path::new("iss.json"), &json, Strictness::Lenient).unwrap();
+        assert!(catalog.entries.is_empty());
+        assert_eq!(catalog.summary.skipped, 1);
+        match &catalog.summary.diagnostics[..] {
+            [
+                TleError::Malformed {
+                    location, fault, ..
+                },
+            ] => {
+                assert_eq!(*location, Location::Record(0));
+                assert_eq!(
+                    *fault,
+                    TleFault::MissingField {
+                        name: "MEAN_MOTION"
+                    }
+                );
+            }
+            other => panic!("unexpected diagnostics {other:?}"),
+        }
+
+        let kvn = ISS_KVN.replace("= SGP4", "= DSST");
+        let catalog = parse_omm_kvn(Path::new("iss.kvn"), &kvn, Strictness::Lenient).unwrap();
+        assert!(catalog.entries.is_empty());
+        assert_eq!(catalog.summary.skipped, 1);
+    }
+
+    #[test]
+    fn rejects_sgp4_xp_elements() {
+        let sgp4_xp = TleFault::Field {
+            name: "MEAN_ELEMENT_THEORY",
+            value: "SGP4-XP".to_string(),
+        };
+        let kvn = ISS_KVN.replace("= SGP4", "= SGP4-XP");
+        match parse_omm_kvn(Path::new("iss.kvn"), &kvn, Strictness::Strict) {
+            Err(TleError::Malformed { fault, .. }) => assert_eq!(fault, sgp4_xp),
+            other => panic!(
+                "expected SGP4-XP to be rejected, got {:?}",
+                other.map(|_| ())
+            ),
+        }
+
+        let xml = ISS_XML.replace(">SGP4<", ">SGP4-XP<");
+        let catalog = parse_omm_xml(Path::new("iss.xml"), &xml, Strictness::Lenient).unwrap();
+        assert!(catalog.entries.is_empty());
+        assert!(matches!(
+            &catalog.summary.diagnostics[..],
+            [TleError::Malformed { fault, .. }] if *fault == sgp4_xp
+        ));
+
+        let old_spelling = ISS_KVN.replace("= SGP4", "= SGP/SGP4");
+        let catalog =
+            parse_omm_kvn(Path::new("iss.kvn"), &old_spelling, Strictness::Strict).unwrap();
+        assert_eq!(catalog.summary.loaded, 1);
+    }
+
+    #[test]
+    fn splits_kvn_messages() {
+        let two = format!("{}\n{}", ISS_KVN, ISS_KVN.replace("25544", "25545"));
+        let catalog = parse_omm_kvn(Path::new("two.kvn"), &two, Strictness::Strict).unwrap();
+        let numbers: Vec<u32> = catalog
+            .entries
+            .iter()
+            .map(|entry| entry.elements.catalog_number)
+            .collect();
+        assert_eq!(numbers, [25544, 25545]);
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        let broken = &ISS_XML[..ISS_XML.len() / 2];
+        assert!(parse_omm_xml(Path::new("iss.xml"), broken, Strictness::Strict).is_err());
+        let catalog = parse_omm_xml(Path::new("iss.xml"), broken, Strictness::Lenient).unwrap();
+        assert_eq!(catalog.summary.skipped, 1);
+
+        let catalog = parse_gp_json(Path::new("iss.json"), "[{", Strictness::Lenient).unwrap();
+        assert!(matches!(
+            &catalog.summary.diagnostics[..],
+            [TleError::Malformed {
+                fault: TleFault::Syntax { .. },
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn sniffs_formats() {
+        assert_eq!(CatalogFormat::sniff(ISS_TLE), CatalogFormat::Tle);
+        assert_eq!(CatalogFormat::sniff(ISS_XML), CatalogFormat::OmmXml);
+        assert_eq!(CatalogFormat::sniff(ISS_KVN), CatalogFormat::OmmKvn);
+        assert_eq!(CatalogFormat::sniff(ISS_JSON), CatalogFormat::GpJson);
+        assert_eq!(CatalogFormat::sniff(ISS_CSV), CatalogFormat::GpCsv);
+        assert_eq!(
+            CatalogFormat::sniff("\n  {\"OBJECT_NAME\": 1}"),
+            CatalogFormat::GpJson
+        );
+
+        // The extension wins, except for ones that don't say.
+        assert_eq!(
+            CatalogFormat::detect(Path::new("gp.JSON"), ISS_TLE),
+            CatalogFormat::GpJson
+        );
+        assert_eq!(
+            CatalogFormat::detect(Path::new("gp.txt"), ISS_CSV),
+            CatalogFormat::GpCsv
+        );
+        assert_eq!(
+            CatalogFormat::detect(Path::new("iss.omm"), ISS_KVN),
+            CatalogFormat::OmmKvn
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+
+use crate::debris::{Debris, DebrisField, SimulationTime, teme_to_world};
+use crate::earth::{DisplayFrame, SceneFrame};
+use crate::frames::gmst;
+use crate::selection::Selection;
+
+/// How much orbit to draw and how often to refresh it.
+#[derive(Debug, Resource)]
+pub struct OrbitTrailSettings {
+    /// Span of each trail in orbital periods, centred on the sim time it was
+    /// computed for.
+    pub revolutions: f64,
+    /// Points per trail.
+    pub samples: usize,
+    /// Recompute once the sim clock has moved this fraction of the span away
+    /// from the time the trail was computed for.
+    pub refresh_fraction: f64,
+}
+
+impl Default for OrbitTrailSettings {
+    fn default() -> Self {
+        Self {
+            revolutions: 1.0,
+            samples: 256,
+            refresh_fraction: 0.1,
+        }
+    }
+}
+
+/// Keeps the orbit of a debris entity drawn after it is deselected.
+#[derive(Component)]
+pub struct Pinned;
+
+/// Sampled orbit path of a debris entity, in scene space. In the Earth-fixed
+/// frame this is the path relative to the ground, not the orbital ellipse.
+#[derive(Component)]
+pub struct OrbitTrail {
+    pub points: Vec<Vec3>,
+    /// Display frame the points were computed in.
+    pub frame: DisplayFrame,
+    /// Full JD the trail is centred on.
+    pub center_jd: f64,
+    /// Span of the trail, days.
+    pub span_days: f64,
+}
+
+/// P pins / unpins the selected object's orbit, Shift+P clears all pins.
+pub fn pin_orbits(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    selection: Res<Selection>,
+    pinned: Query<Entity, With<Pinned>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyP) {
+        return;
+    }
+
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        for entity in &pinned {
+            commands.entity(entity).remove::<Pinned>();
+        }
+        return;
+    }
+
+    if let Some(entity) = selection.selected {
+        if pinned.contains(entity) {
+            commands.entity(entity).remove::<Pinned>();
+        } else {
+            commands.entity(entity).insert(Pinned);
+        }
+    }
+}
+
+/// (Re)compute trails for the selected and pinned objects, and drop trails
+/// from everything else.
+pub fn update_orbit_trails(
+    mut commands: Commands,
+    settings: Res<OrbitTrailSettings>,
+    sim_time: Res<SimulationTime>,
+    scene: SceneFrame,
+    selection: Res<Selection>,
+    mut debris_field: ResMut<DebrisField>,
+    mut debris: Query<(Entity, &Debris, Has<Pinned>, Option<&mut OrbitTrail>)>,
+) {
+    let now_jd = sim_time.jd + sim_time.fr;
+
+    for (entity, debris, pinned, trail) in &mut debris {
+        let wanted = pinned || selection.selected == Some(entity);
+        if !wanted {
+            if trail.is_some() {
+                commands.entity(entity).remove::<OrbitTrail>();
+            }
+            continue;
+        }
+
+        if let Some(trail) = &trail
+            && trail.frame == *scene.frame
+            && (now_jd - trail.center_jd).abs() < trail.span_days * settings.refresh_fraction
+        {
+            continue;
+        }
+
+        let Some(sat) = debris_field.sats.get_mut(debris.sat_index) else {
+            continue;
+        };
+        let span_days = settings.revolutions * sat.elements.period_minutes() / 1_440.0;
+        let samples = settings.samples.max(2);
+        let step = span_days / (samples - 1) as f64;
+        let start_jd = now_jd - span_days / 2.0;
+
+        // Samples SGP4 can't propagate (e.g. past decay) are simply skipped.
+        let points = (0..samples)
+            .filter_map(|i| {
+                let jd_full = start_jd + i as f64 * step;
+                let jd = jd_full.floor();
+                let fr = jd_full - jd;
+                let (_err, r_km, _v_km_s) = sat.satrec.sgp4(jd, fr).ok()?;
+                let to_display = scene.inertial_to_display_at(gmst(jd, fr));
+                Some(to_display * teme_to_world(&r_km))
+            })
+            .collect();
+
+        let new_trail = OrbitTrail {
+            points,
+            frame: *scene.frame,
+            center_jd: now_jd,
+            span_days,
+        };
+        match trail {
+            Some(mut trail) => *trail = new_trail,
+            None => {
+                commands.entity(entity).insert(new_trail);
+            }
+        }
+    }
+}
+
+/// Draw every trail as a polyline; pinned orbits in cyan, the selection in
+/// yellow.
+pub fn draw_orbit_trails(
+    selection: Res<Selection>,
+    trails: Query<(Entity, &OrbitTrail)>,
+    mut gizmos: Gizmos,
+) {
+    for (entity, trail) in &trails {
+        let color = if selection.selected == Some(entity) {
+            Color::srgb(1.0, 0.9, 0.2)
+        } else {
+            Color::srgb(0.2, 0.8, 1.0)
+        };
+        gizmos.linestrip(trail.points.iter().copied(), color);
+    }
+}
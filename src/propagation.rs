@@ -0,0 +1,396 @@
+use bevy::{
+    prelude::*,
+    tasks::{ComputeTaskPool, ParallelSliceMut},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::debris::{Debris, DebrisField, DebrisSat, SimulationTime, teme_to_world};
+use crate::earth::SceneFrame;
+use crate::filtering::FilteredOut;
+
+/// TEME position (km) and velocity (km/s) of one object.
+#[derive(Debug, Clone, Copy)]
+pub struct StateVector {
+    pub r: [f64; 3],
+    pub v: [f64; 3],
+}
+
+/// Why SGP4 couldn't propagate an object.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropagationError {
+    /// Non-zero SGP4 error code (Vallado's numbering).
+    Code(i32),
+    /// Any other failure reported by the propagator.
+    Other(String),
+}
+
+impl PropagationError {
+    /// SGP4 error code, if there is one.
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            PropagationError::Code(code) => Some(*code),
+            PropagationError::Other(_) => None,
+        }
+    }
+
+    pub fn is_decay(&self) -> bool {
+        self.code() == Some(6)
+    }
+}
+
+impl std::fmt::Display for PropagationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PropagationError::Code(code) => {
+                let reason = match code {
+                    1 => "mean eccentricity out of range or semi-major axis below 0.95 Earth radii",
+                    2 => "negative mean motion",
+                    3 => "perturbed eccentricity out of range",
+                    4 => "negative semi-latus rectum",
+                    5 => "epoch elements are sub-orbital",
+                    6 => "orbit has decayed",
+                    _ => "unknown error",
+                };
+                write!(f, "SGP4 error {}: {}", code, reason)
+            }
+            PropagationError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Result of propagating one object.
+pub type Propagated = Result<StateVector, PropagationError>;
+
+/// Propagate one object, treating a non-zero SGP4 error code as a failure.
+pub fn propagate(sat: &mut DebrisSat, jd: f64, fr: f64) -> Propagated {
+    match sat.satrec.sgp4(jd, fr) {
+        Ok((0, r, v)) => Ok(StateVector { r, v }),
+        Ok((code, _, _)) => Err(PropagationError::Code(code)),
+        Err(message) => Err(PropagationError::Other(message)),
+    }
+}
+
+/// Golden-section search for the time in `[start, end]` (full JD) at which
+/// `f` is smallest, to within `tolerance_days`. `f` should have a single
+/// minimum in the interval.
+pub fn golden_section_min(
+    mut f: impl FnMut(f64) -> f64,
+    start: f64,
+    end: f64,
+    tolerance_days: f64,
+) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut lo, mut hi) = (start, end);
+    let mut x1 = hi - ratio * (hi - lo);
+    let mut x2 = lo + ratio * (hi - lo);
+    let (mut f1, mut f2) = (f(x1), f(x2));
+    while hi - lo > tolerance_days {
+        if f1 < f2 {
+            hi = x2;
+            (x2, f2) = (x1, f1);
+            x1 = hi - ratio * (hi - lo);
+            f1 = f(x1);
+        } else {
+            lo = x1;
+            (x1, f1) = (x2, f2);
+            x2 = lo + ratio * (hi - lo);
+            f2 = f(x2);
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+/// Propagate every object on the calling thread.
+pub fn propagate_serial(sats: &mut [DebrisSat], jd: f64, fr: f64) -> Vec<Propagated> {
+    sats.iter_mut().map(|sat| propagate(sat, jd, fr)).collect()
+}
+
+/// Propagate every object, split across the compute task pool. Results are
+/// in `sats` order.
+pub fn propagate_parallel(mut sats: &mut [DebrisSat], jd: f64, fr: f64) -> Vec<Propagated> {
+    sats.par_splat_map_mut(ComputeTaskPool::get(), None, |_, chunk| {
+        propagate_serial(chunk, jd, fr)
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// How often the debris field is propagated.
+#[derive(Debug, Default, Resource)]
+pub struct PropagationSettings {
+    /// Propagations per second of real time, interpolating in between.
+    /// `None` propagates every frame.
+    pub rate_hz: Option<f64>,
+}
+
+/// Every object's state at one instant.
+#[derive(Debug)]
+pub struct Snapshot {
+    /// `DebrisField::generation` the states were computed for.
+    pub generation: u64,
+    /// Julian date, split like `SimulationTime`.
+    pub jd: f64,
+    pub fr: f64,
+    /// Indexed like `DebrisField::sats`.
+    pub states: Vec<Propagated>,
+}
+
+impl Snapshot {
+    fn take(debris_field: &mut DebrisField, jd_full: f64) -> Self {
+        let jd = jd_full.floor();
+        let fr = jd_full - jd;
+        Self {
+            generation: debris_field.generation,
+            jd,
+            fr,
+            states: propagate_parallel(&mut debris_field.sats, jd, fr),
+        }
+    }
+
+    /// Days from this snapshot to `(jd, fr)`.
+    fn days_until(&self, jd: f64, fr: f64) -> f64 {
+        (jd - self.jd) + (fr - self.fr)
+    }
+}
+
+/// Propagated states of the debris field: a snapshot at or just before the
+/// sim time and, when propagating at a fixed rate, the next one after it.
+#[derive(Debug, Default, Resource)]
+pub struct DebrisStates {
+    pub current: Option<Snapshot>,
+    pub next: Option<Snapshot>,
+}
+
+impl DebrisStates {
+    /// TEME position of object `index` at `(jd, fr)`: cubic Hermite
+    /// interpolation between the two snapshots, or the current snapshot as
+    /// is.
+    pub fn position(
+        &self,
+        index: usize,
+        jd: f64,
+        fr: f64,
+    ) -> Option<Result<[f64; 3], &PropagationError>> {
+        let current = self.current.as_ref()?;
+        let a = match current.states.get(index)? {
+            Ok(state) => state,
+            Err(e) => return Some(Err(e)),
+        };
+        let Some(next) = &self.next else {
+            return Some(Ok(a.r));
+        };
+        let b = match next.states.get(index)? {
+            Ok(state) => state,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let span_days = current.days_until(next.jd, next.fr);
+        let t = current.days_until(jd, fr) / span_days;
+        let h = span_days * 86_400.0;
+        let (t2, t3) = (t * t, t * t * t);
+        let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+        let h10 = t3 - 2.0 * t2 + t;
+        let h01 = -2.0 * t3 + 3.0 * t2;
+        let h11 = t3 - t2;
+        Some(Ok(std::array::from_fn(|i| {
+            h00 * a.r[i] + h10 * h * a.v[i] + h01 * b.r[i] + h11 * h * b.v[i]
+        })))
+    }
+
+    /// Whether `(jd, fr)` lies between the snapshots (or on the single one).
+    fn covers(&self, jd: f64, fr: f64, generation: u64) -> bool {
+        let Some(current) = &self.current else {
+            return false;
+        };
+        if current.generation != generation {
+            return false;
+        }
+        let offset = current.days_until(jd, fr);
+        match &self.next {
+            None => offset == 0.0,
+            Some(next) => {
+                let span = current.days_until(next.jd, next.fr);
+                next.generation == generation && (0.0..=1.0).contains(&(offset / span))
+            }
+        }
+    }
+}
+
+/// Bring `DebrisStates` up to the sim time. Every frame without a rate; with
+/// one, only when the sim clock leaves the current pair of snapshots, which
+/// are spaced by the sim time that passes in one propagation period.
+pub fn propagate_debris(
+    sim_time: Res<SimulationTime>,
+    settings: Res<PropagationSettings>,
+    mut debris_field: ResMut<DebrisField>,
+    mut states: ResMut<DebrisStates>,
+) {
+    let (jd, fr) = (sim_time.jd, sim_time.fr);
+    let generation = debris_field.generation;
+    if states.covers(jd, fr, generation) {
+        return;
+    }
+    let debris_field = &mut *debris_field;
+    let now = jd + fr;
+
+    let step_days = settings
+        .rate_hz
+        .map(|hz| sim_time.time_scale / hz / 86_400.0)
+        .filter(|step| *step != 0.0);
+    let Some(step_days) = step_days else {
+        states.current = Some(Snapshot::take(debris_field, now));
+        states.next = None;
+        return;
+    };
+
+    // Played on past the next snapshot, by less than a step: it becomes the
+    // current one.
+    if let Some(next) = states.next.take()
+        && next.generation == generation
+        && (0.0..1.0).contains(&(next.days_until(jd, fr) / step_days))
+    {
+        let following = next.jd + next.fr + step_days;
+        states.current = Some(next);
+        states.next = Some(Snapshot::take(debris_field, following));
+        return;
+    }
+
+    states.current = Some(Snapshot::take(debris_field, now));
+    states.next = Some(Snapshot::take(debris_field, now + step_days));
+}
+
+/// Whether an object could be propagated at the current sim time.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub enum PropagationStatus {
+    #[default]
+    Nominal,
+    Failed {
+        error: PropagationError,
+        /// Sim time the failure was first seen at.
+        since: DateTime<Utc>,
+    },
+}
+
+impl PropagationStatus {
+    pub fn is_decayed(&self) -> bool {
+        matches!(self, PropagationStatus::Failed { error, .. } if error.is_decay())
+    }
+}
+
+/// Marker for the failed-objects counter.
+#[derive(Component)]
+pub struct PropagationCounter;
+
+/// What `update_debris_positions` touches on each debris entity.
+type DebrisUpdate<'a> = (
+    &'a Debris,
+    &'a Name,
+    &'a mut Transform,
+    &'a mut Visibility,
+    &'a mut PropagationStatus,
+    Has<FilteredOut>,
+);
+
+/// Move debris entities to their (interpolated) positions, in parallel.
+/// Objects that fail to propagate are hidden until they propagate again (and
+/// then only shown if the filter lets them), with one log line each way.
+pub fn update_debris_positions(
+    sim_time: Res<SimulationTime>,
+    scene: SceneFrame,
+    states: Res<DebrisStates>,
+    mut query: Query<DebrisUpdate>,
+) {
+    let (jd, fr) = (sim_time.jd, sim_time.fr);
+    let to_display = scene.inertial_to_display();
+
+    query.par_iter_mut().for_each(
+        |(debris, name, mut transform, mut visibility, mut status, filtered_out)| match states
+            .position(debris.sat_index, jd, fr)
+        {
+            Some(Ok(r_km)) => {
+                transform.translation = to_display * teme_to_world(&r_km);
+                if *status != PropagationStatus::Nominal {
+                    info!("{}: propagating again at {}", name, sim_time.utc());
+                    *status = PropagationStatus::Nominal;
+                    if !filtered_out {
+                        *visibility = Visibility::Inherited;
+                    }
+                }
+            }
+            Some(Err(error)) => {
+                let since = match &*status {
+                    PropagationStatus::Failed {
+                        error: previous, ..
+                    } if previous == error => return,
+                    // Still failing, for another reason: keep the time of
+                    // the first failure.
+                    PropagationStatus::Failed { since, .. } => {
+                        warn!(
+                            "{}: propagation now failing at {}: {}",
+                            name,
+                            sim_time.utc(),
+                            error
+                        );
+                        *since
+                    }
+                    PropagationStatus::Nominal => {
+                        let since = sim_time.utc();
+                        warn!("{}: propagation failed at {}: {}", name, since, error);
+                        since
+                    }
+                };
+                *status = PropagationStatus::Failed {
+                    error: error.clone(),
+                    since,
+                };
+                *visibility = Visibility::Hidden;
+            }
+            None => {}
+        },
+    );
+}
+
+/// Spawn the failed-objects counter above the timeline.
+pub fn setup_propagation_counter(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Propagation Counter"),
+        PropagationCounter,
+        Text::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(64.0),
+            left: Val::Px(12.0),
+            ..default()
+        },
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.6, 0.4)),
+    ));
+}
+
+/// Count objects that currently fail to propagate (hidden when there are
+/// none).
+pub fn update_propagation_counter(
+    statuses: Query<&PropagationStatus>,
+    counter: Single<(&mut Text, &mut Visibility), With<PropagationCounter>>,
+) {
+    let (mut text, mut visibility) = counter.into_inner();
+
+    let (failed, decayed) = statuses
+        .iter()
+        .filter(|status| **status != PropagationStatus::Nominal)
+        .fold((0, 0), |(failed, decayed), status| {
+            (failed + 1, decayed + status.is_decayed() as usize)
+        });
+    if failed == 0 {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    }
+
+    text.0 = format!("Not propagating: {} ({} decayed)", failed, decayed);
+    visibility.set_if_neq(Visibility::Inherited);
+}
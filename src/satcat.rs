@@ -0,0 +1,465 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use bevy::asset::{AssetLoader, LoadContext, io::Reader};
+use bevy::prelude::*;
+use chrono::NaiveDate;
+
+use crate::debris::{Debris, DebrisField};
+use crate::loader::{LoadSummary, Location, TleError, TleFault};
+
+/// Kind of object, from the SATCAT `OBJECT_TYPE` or guessed from its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectType {
+    Payload,
+    RocketBody,
+    Debris,
+    Unknown,
+}
+
+impl ObjectType {
+    pub const ALL: [ObjectType; 4] = [
+        ObjectType::Payload,
+        ObjectType::RocketBody,
+        ObjectType::Debris,
+        ObjectType::Unknown,
+    ];
+
+    /// SATCAT `OBJECT_TYPE` code: `PAY`, `R/B`, `DEB` or `UNK`.
+    fn from_code(code: &str) -> Self {
+        match code {
+            "PAY" => ObjectType::Payload,
+            "R/B" => ObjectType::RocketBody,
+            "DEB" => ObjectType::Debris,
+            _ => ObjectType::Unknown,
+        }
+    }
+
+    /// Guess from a CelesTrak-style object name: `... R/B`, `... DEB`, and
+    /// unidentified `OBJECT A` style names. Anything else named is taken to
+    /// be a payload.
+    pub fn from_name(name: Option<&str>) -> Self {
+        let Some(name) = name.map(str::to_uppercase) else {
+            return ObjectType::Unknown;
+        };
+        if name.contains("R/B") || name.contains("AKM") || name.contains("PKM") {
+            ObjectType::RocketBody
+        } else if name.contains("DEB") || name.contains("COOLANT") || name.contains("FRAG") {
+            ObjectType::Debris
+        } else if name.starts_with("OBJECT ") || name.starts_with("TBA") {
+            ObjectType::Unknown
+        } else {
+            ObjectType::Payload
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ObjectType::Payload => "Payload",
+            ObjectType::RocketBody => "Rocket body",
+            ObjectType::Debris => "Debris",
+            ObjectType::Unknown => "Unknown",
+        }
+    }
+}
+
+/// Radar cross-section class, using CelesTrak's thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RcsSize {
+    /// Below 0.1 m².
+    Small,
+    /// 0.1 to 1 m².
+    Medium,
+    /// Above 1 m².
+    Large,
+}
+
+impl RcsSize {
+    fn from_m2(rcs: f64) -> Self {
+        if rcs < 0.1 {
+            RcsSize::Small
+        } else if rcs <= 1.0 {
+            RcsSize::Medium
+        } else {
+            RcsSize::Large
+        }
+    }
+
+    /// Older exports' `RCS_SIZE` column.
+    fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "SMALL" => Some(RcsSize::Small),
+            "MEDIUM" => Some(RcsSize::Medium),
+            "LARGE" => Some(RcsSize::Large),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RcsSize::Small => "small",
+            RcsSize::Medium => "medium",
+            RcsSize::Large => "large",
+        }
+    }
+}
+
+/// What the SATCAT knows about an object, attached to its `Debris` entity
+/// when a SATCAT is loaded and lists its catalog number.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct SatelliteMetadata {
+    pub name: String,
+    /// Owner / country code, e.g. `US`, `PRC`, `CIS`.
+    pub owner: String,
+    pub launch_date: Option<NaiveDate>,
+    /// Launch site code, e.g. `AFETR`, `TYMSC`.
+    pub launch_site: String,
+    pub object_type: ObjectType,
+    /// Radar cross-section, m², if published.
+    pub rcs: Option<f64>,
+    pub rcs_size: Option<RcsSize>,
+    pub decay_date: Option<NaiveDate>,
+}
+
+/// A parsed SATCAT, keyed by NORAD catalog number. Also the asset type
+/// produced by [`SatcatLoader`].
+#[derive(Asset, TypePath)]
+pub struct Satcat {
+    pub records: HashMap<u32, SatelliteMetadata>,
+    pub summary: LoadSummary,
+}
+
+/// Loads the CelesTrak SATCAT CSV export as a [`Satcat`] asset. Plain `.csv`
+/// belongs to [`TleCatalogLoader`](crate::loader::TleCatalogLoader), so this
+/// only claims `.satcat.csv`; [`load_satcat`] asks for a `Satcat` by type,
+/// which picks this loader whatever the file is called.
+#[derive(Default)]
+pub struct SatcatLoader;
+
+impl AssetLoader for SatcatLoader {
+    type Asset = Satcat;
+    type Settings = ();
+    type Error = TleError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Satcat, TleError> {
+        let path = load_context.path().to_path_buf();
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|source| TleError::Io {
+                path: path.clone(),
+                source,
+            })?;
+
+        Ok(parse_satcat(&path, &String::from_utf8_lossy(&bytes)))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["satcat.csv"]
+    }
+}
+
+/// Parse a SATCAT CSV. Malformed rows are skipped and reported in the
+/// summary; `path` is only used for diagnostics.
+pub fn parse_satcat(path: &Path, text: &str) -> Satcat {
+    let mut satcat = Satcat {
+        records: HashMap::new(),
+        summary: LoadSummary::default(),
+    };
+    let reject = |summary: &mut LoadSummary, line: usize, fault: TleFault| {
+        summary.skipped += 1;
+        summary.diagnostics.push(TleError::Malformed {
+            path: path.to_path_buf(),
+            location: Location::Line(line),
+            fault,
+        });
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            let fault = TleFault::Syntax {
+                message: e.to_string(),
+            };
+            reject(&mut satcat.summary, 1, fault);
+            return satcat;
+        }
+    };
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|p| p.line() as usize).unwrap_or(0);
+                let fault = TleFault::Syntax {
+                    message: e.to_string(),
+                };
+                reject(&mut satcat.summary, line, fault);
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+        let fields: HashMap<&str, &str> = headers
+            .iter()
+            .zip(record.iter())
+            .filter(|(_, value)| !value.is_empty())
+            .collect();
+        match metadata_from_fields(&fields) {
+            Ok((catalog_number, metadata)) => {
+                satcat.records.insert(catalog_number, metadata);
+                satcat.summary.loaded += 1;
+            }
+            Err(fault) => reject(&mut satcat.summary, line, fault),
+        }
+    }
+
+    satcat
+}
+
+fn metadata_from_fields(
+    fields: &HashMap<&str, &str>,
+) -> Result<(u32, SatelliteMetadata), TleFault> {
+    let text = |name: &str| fields.get(name).map(|v| v.to_string()).unwrap_or_default();
+    let date = |name: &'static str| {
+        fields
+            .get(name)
+            .map(|value| {
+                NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| TleFault::Field {
+                    name,
+                    value: value.to_string(),
+                })
+            })
+            .transpose()
+    };
+
+    let catalog_number = fields
+        .get("NORAD_CAT_ID")
+        .ok_or(TleFault::MissingField {
+            name: "NORAD_CAT_ID",
+        })?
+        .parse::<u32>()
+        .map_err(|_| TleFault::Field {
+            name: "NORAD_CAT_ID",
+            value: text("NORAD_CAT_ID"),
+        })?;
+    // Current exports give the RCS in m²; older ones only a size class.
+    let rcs = fields.get("RCS").and_then(|v| v.parse::<f64>().ok());
+    let rcs_size = rcs
+        .map(RcsSize::from_m2)
+        .or_else(|| fields.get("RCS_SIZE").and_then(|v| RcsSize::from_label(v)));
+
+    Ok((
+        catalog_number,
+        SatelliteMetadata {
+            name: text("OBJECT_NAME"),
+            owner: text("OWNER"),
+            launch_date: date("LAUNCH_DATE")?,
+            launch_site: text("LAUNCH_SITE"),
+            object_type: fields
+                .get("OBJECT_TYPE")
+                .map_or(ObjectType::Unknown, |code| ObjectType::from_code(code)),
+            rcs,
+            rcs_size,
+            decay_date: date("DECAY_DATE")?,
+        },
+    ))
+}
+
+/// Asset path of the SATCAT to join against, if one was given.
+#[derive(Debug, Default, Resource)]
+pub struct SatcatSource(pub Option<String>);
+
+/// Handle of the loaded SATCAT.
+#[derive(Resource)]
+pub struct SatcatHandle(pub Handle<Satcat>);
+
+pub fn load_satcat(
+    mut commands: Commands,
+    source: Res<SatcatSource>,
+    asset_server: Res<AssetServer>,
+) {
+    if let Some(path) = &source.0 {
+        commands.insert_resource(SatcatHandle(asset_server.load::<Satcat>(path.clone())));
+    }
+}
+
+/// Join the SATCAT onto the debris entities by catalog number whenever it
+/// (re)loads or new entities are spawned. Objects it doesn't list lose any
+/// metadata they had.
+pub fn attach_satellite_metadata(
+    mut commands: Commands,
+    mut events: MessageReader<AssetEvent<Satcat>>,
+    handle: Option<Res<SatcatHandle>>,
+    satcats: Res<Assets<Satcat>>,
+    debris_field: Res<DebrisField>,
+    added: Query<(), Added<Debris>>,
+    debris: Query<(Entity, &Debris)>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+    let mut reloaded = false;
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event
+            && *id == handle.0.id()
+        {
+            reloaded = true;
+        }
+    }
+    let Some(satcat) = satcats.get(&handle.0) else {
+        return;
+    };
+    if reloaded {
+        let summary = &satcat.summary;
+        for diagnostic in &summary.diagnostics {
+            warn!("{}", diagnostic);
+        }
+        info!(
+            "Loaded SATCAT: {} records ({} skipped)",
+            summary.loaded, summary.skipped
+        );
+    } else if added.is_empty() {
+        return;
+    }
+
+    let mut matched = 0;
+    for (entity, debris) in &debris {
+        let Some(sat) = debris_field.sats.get(debris.sat_index) else {
+            continue;
+        };
+        match satcat.records.get(&sat.elements.catalog_number) {
+            Some(metadata) => {
+                commands.entity(entity).insert(metadata.clone());
+                matched += 1;
+            }
+            None => {
+                commands.entity(entity).remove::<SatelliteMetadata>();
+            }
+        }
+    }
+    info!(
+        "SATCAT metadata for {} of {} objects",
+        matched,
+        debris_field.sats.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SATCAT: &str = "\
+OBJECT_NAME,OBJECT_ID,NORAD_CAT_ID,OBJECT_TYPE,OPS_STATUS_CODE,OWNER,LAUNCH_DATE,LAUNCH_SITE,DECAY_DATE,PERIOD,INCLINATION,APOGEE,PERIGEE,RCS,DATA_STATUS_CODE,ORBIT_CENTER,ORBIT_TYPE
+ISS (ZARYA),1998-067A,25544,PAY,+,ISS,1998-11-20,TYMSC,,92.89,51.64,418,414,399.0524,,EA,ORB
+COSMOS 2251 DEB,1993-036SX,34427,DEB,,CIS,1993-06-16,PKMTR,2022-03-18,97.41,74.01,0,0,0.0341,,EA,IMP
+SL-16 R/B,1994-023B,23088,R/B,,CIS,1994-04-26,TYMSC,,101.59,71.0,856,835,,,EA,ORB
+";
+
+    fn parse(text: &str) -> Satcat {
+        parse_satcat(Path::new("satcat.csv"), text)
+    }
+
+    #[test]
+    fn parses_celestrak_export() {
+        let satcat = parse(SATCAT);
+        assert_eq!(satcat.summary.loaded, 3);
+        assert_eq!(satcat.summary.skipped, 0);
+
+        let iss = &satcat.records[&25544];
+        assert_eq!(iss.name, "ISS (ZARYA)");
+        assert_eq!(iss.owner, "ISS");
+        assert_eq!(iss.launch_date, NaiveDate::from_ymd_opt(1998, 11, 20));
+        assert_eq!(iss.launch_site, "TYMSC");
+        assert_eq!(iss.object_type, ObjectType::Payload);
+        assert_eq!(iss.rcs, Some(399.0524));
+        assert_eq!(iss.rcs_size, Some(RcsSize::Large));
+        assert_eq!(iss.decay_date, None);
+
+        let debris = &satcat.records[&34427];
+        assert_eq!(debris.object_type, ObjectType::Debris);
+        assert_eq!(debris.rcs_size, Some(RcsSize::Small));
+        assert_eq!(debris.decay_date, NaiveDate::from_ymd_opt(2022, 3, 18));
+
+        let rocket_body = &satcat.records[&23088];
+        assert_eq!(rocket_body.object_type, ObjectType::RocketBody);
+        assert_eq!(rocket_body.rcs, None);
+        assert_eq!(rocket_body.rcs_size, None);
+    }
+
+    #[test]
+    fn reads_older_rcs_size_column() {
+        let satcat = parse("NORAD_CAT_ID,OBJECT_TYPE,RCS_SIZE\n900,PAY,MEDIUM\n901,UNK,\n");
+        assert_eq!(satcat.records[&900].rcs_size, Some(RcsSize::Medium));
+        assert_eq!(satcat.records[&901].rcs_size, None);
+        assert_eq!(satcat.records[&901].object_type, ObjectType::Unknown);
+    }
+
+    #[test]
+    fn skips_malformed_rows() {
+        let text = "NORAD_CAT_ID,OBJECT_NAME,LAUNCH_DATE\n\
+                    5,VANGUARD 1,1958-03-17\n\
+                    ,NO NUMBER,1958-03-17\n\
+                    x6,BAD NUMBER,1958-03-17\n\
+                    7,BAD DATE,17/03/1958\n";
+        let satcat = parse(text);
+        assert_eq!(satcat.summary.loaded, 1);
+        assert_eq!(satcat.summary.skipped, 3);
+        let faults: Vec<_> = satcat
+            .summary
+            .diagnostics
+            .iter()
+            .map(|diagnostic| match diagnostic {
+                TleError::Malformed {
+                    location: Location::Line(line),
+                    fault,
+                    ..
+                } => (*line, fault.clone()),
+                other => panic!("unexpected diagnostic {other}"),
+            })
+            .collect();
+        assert_eq!(
+            faults,
+            [
+                (
+                    3,
+                    TleFault::MissingField {
+                        name: "NORAD_CAT_ID"
+                    }
+                ),
+                (
+                    4,
+                    TleFault::Field {
+                        name: "NORAD_CAT_ID",
+                        value: "x6".to_string()
+                    }
+                ),
+                (
+                    5,
+                    TleFault::Field {
+                        name: "LAUNCH_DATE",
+                        value: "17/03/1958".to_string()
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn guesses_type_from_name() {
+        let guess = |name| ObjectType::from_name(Some(name));
+        assert_eq!(guess("CZ-4C R/B"), ObjectType::RocketBody);
+        assert_eq!(guess("Fengyun 1C deb"), ObjectType::Debris);
+        assert_eq!(guess("OBJECT A"), ObjectType::Unknown);
+        assert_eq!(guess("STARLINK-1007"), ObjectType::Payload);
+        assert_eq!(ObjectType::from_name(None), ObjectType::Unknown);
+    }
+}
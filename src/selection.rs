@@ -0,0 +1,297 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::debris::{DEBRIS_RADIUS, Debris, DebrisField, EARTH_RADIUS_KM, SimulationTime};
+use crate::earth::EarthRotation;
+use crate::frames::Geodetic;
+use crate::propagation::{PropagationStatus, StateVector, propagate};
+use crate::satcat::SatelliteMetadata;
+
+/// Smallest pick radius, world units.
+const PICK_RADIUS: f32 = DEBRIS_RADIUS;
+/// Angular pick tolerance in radians, so distant dots stay clickable.
+const PICK_ANGLE: f32 = 0.008;
+/// A press that moves further than this (px) is a camera drag, not a click.
+const CLICK_SLOP_PX: f32 = 4.0;
+
+/// Currently selected and hovered debris entities.
+#[derive(Debug, Default, Resource)]
+pub struct Selection {
+    pub selected: Option<Entity>,
+    pub hovered: Option<Entity>,
+}
+
+/// Marker for the side panel with the selected object's details.
+#[derive(Component)]
+pub struct InfoPanel;
+
+/// Marker for the name tooltip that follows the cursor.
+#[derive(Component)]
+pub struct HoverTooltip;
+
+/// Cast a ray from the cursor through the `OrbitCamera` and return the
+/// nearest debris entity it hits that isn't hidden behind the Earth.
+fn pick(
+    ray: Ray3d,
+    debris: &Query<(Entity, &GlobalTransform, &InheritedVisibility), With<Debris>>,
+) -> Option<Entity> {
+    let origin = ray.origin;
+    let dir = *ray.direction;
+
+    // Entry distance into the unit Earth sphere, if the ray hits it.
+    let b = origin.dot(dir);
+    let c = origin.length_squared() - 1.0;
+    let disc = b * b - c;
+    let earth_t = (disc > 0.0)
+        .then(|| -b - disc.sqrt())
+        .filter(|t| *t > 0.0)
+        .unwrap_or(f32::INFINITY);
+
+    debris
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get())
+        .filter_map(|(entity, transform, _)| {
+            let to_center = transform.translation() - origin;
+            let t = to_center.dot(dir);
+            if t <= 0.0 || t > earth_t {
+                return None;
+            }
+            let miss_sq = to_center.length_squared() - t * t;
+            let radius = PICK_RADIUS.max(t * PICK_ANGLE);
+            (miss_sq <= radius * radius).then_some((entity, t))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+}
+
+/// Hover on mouse move, select on left click (a press/release without
+/// dragging). Clicking empty space clears the selection. The cursor is over
+/// the UI when any node with an `Interaction` is hovered, so every panel
+/// carries one.
+pub fn pick_debris(
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    ui_interactions: Query<&Interaction>,
+    debris: Query<(Entity, &GlobalTransform, &InheritedVisibility), With<Debris>>,
+    mut selection: ResMut<Selection>,
+    mut press_position: Local<Option<Vec2>>,
+) {
+    let Some(cursor) = window.cursor_position() else {
+        selection.hovered = None;
+        return;
+    };
+    let (camera, camera_transform) = *camera;
+    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor) else {
+        return;
+    };
+
+    let over_ui = ui_interactions.iter().any(|i| *i != Interaction::None);
+    selection.hovered = if over_ui { None } else { pick(ray, &debris) };
+
+    if mouse_buttons.just_pressed(MouseButton::Left) {
+        *press_position = (!over_ui).then_some(cursor);
+    }
+    if mouse_buttons.just_released(MouseButton::Left)
+        && let Some(pressed_at) = press_position.take()
+        && pressed_at.distance(cursor) <= CLICK_SLOP_PX
+    {
+        selection.selected = selection.hovered;
+    }
+}
+
+/// Escape clears the selection.
+pub fn clear_selection(keys: Res<ButtonInput<KeyCode>>, mut selection: ResMut<Selection>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        selection.selected = None;
+    }
+}
+
+/// Outline the selected object (and, more faintly, the hovered one).
+pub fn draw_selection_highlight(
+    selection: Res<Selection>,
+    debris: Query<&GlobalTransform, With<Debris>>,
+    mut gizmos: Gizmos,
+) {
+    if let Some(transform) = selection.selected.and_then(|e| debris.get(e).ok()) {
+        gizmos.sphere(
+            Isometry3d::from_translation(transform.translation()),
+            0.06,
+            Color::srgb(1.0, 0.9, 0.2),
+        );
+    }
+    if selection.hovered != selection.selected
+        && let Some(transform) = selection.hovered.and_then(|e| debris.get(e).ok())
+    {
+        gizmos.sphere(
+            Isometry3d::from_translation(transform.translation()),
+            0.05,
+            Color::srgba(1.0, 1.0, 1.0, 0.5),
+        );
+    }
+}
+
+/// Spawn the (initially hidden) info panel and hover tooltip.
+pub fn setup_selection_ui(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Info Panel"),
+        InfoPanel,
+        Text::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(48.0),
+            right: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Interaction::default(),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Visibility::Hidden,
+    ));
+
+    commands.spawn((
+        Name::new("Hover Tooltip"),
+        HoverTooltip,
+        Text::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Visibility::Hidden,
+    ));
+}
+
+/// Fill the info panel for the selected object. Altitude, speed and the
+/// sub-satellite point come from propagating it to the current sim time.
+pub fn update_info_panel(
+    mut selection: ResMut<Selection>,
+    sim_time: Res<SimulationTime>,
+    earth_rotation: Res<EarthRotation>,
+    mut debris_field: ResMut<DebrisField>,
+    debris: Query<(&Debris, &PropagationStatus, Option<&SatelliteMetadata>)>,
+    panel: Single<(&mut Text, &mut Visibility), With<InfoPanel>>,
+) {
+    let (mut text, mut visibility) = panel.into_inner();
+
+    let Some(entity) = selection.selected else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    // The entity can disappear under us when the catalog is reloaded.
+    let Some((sat, status, metadata)) =
+        debris.get(entity).ok().and_then(|(d, status, metadata)| {
+            debris_field
+                .sats
+                .get_mut(d.sat_index)
+                .map(|sat| (sat, status, metadata))
+        })
+    else {
+        selection.selected = None;
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    let elements = &sat.elements;
+    let name = sat
+        .name
+        .clone()
+        .or_else(|| metadata.map(|m| m.name.clone()))
+        .unwrap_or_else(|| "(unnamed)".to_string());
+    let mut lines = vec![
+        name,
+        format!("Catalog #: {}", elements.catalog_number),
+        format!("Int'l designator: {}", elements.object_id),
+        format!(
+            "TLE epoch: {}",
+            elements.epoch.format("%Y-%m-%d %H:%M:%S UTC")
+        ),
+        format!("Inclination: {:.4}°", elements.inclination),
+        format!("Eccentricity: {:.7}", elements.eccentricity),
+        format!(
+            "Mean motion: {:.8} rev/day ({:.1} min)",
+            elements.mean_motion,
+            elements.period_minutes()
+        ),
+        format!("Perigee: {:.1} km", elements.perigee_altitude_km()),
+        format!("Apogee: {:.1} km", elements.apogee_altitude_km()),
+    ];
+    if let Some(metadata) = metadata {
+        lines.extend(metadata_lines(metadata));
+    }
+    match propagate(sat, sim_time.jd, sim_time.fr) {
+        Ok(StateVector { r: r_km, v: v_km_s }) => {
+            let r = (r_km[0] * r_km[0] + r_km[1] * r_km[1] + r_km[2] * r_km[2]).sqrt();
+            let v = (v_km_s[0] * v_km_s[0] + v_km_s[1] * v_km_s[1] + v_km_s[2] * v_km_s[2]).sqrt();
+            let geodetic =
+                Geodetic::from_itrf(earth_rotation.teme_to_itrf(r_km, sim_time.jd, sim_time.fr));
+            lines.push(format!("Altitude: {:.1} km", r - EARTH_RADIUS_KM));
+            lines.push(format!("Speed: {:.3} km/s", v));
+            lines.push(format!(
+                "Sub-satellite point: {:.3}°, {:.3}° ({:.1} km geodetic)",
+                geodetic.latitude.to_degrees(),
+                geodetic.longitude.to_degrees(),
+                geodetic.altitude_km
+            ));
+        }
+        Err(e) => lines.push(format!("Propagation failed: {}", e)),
+    }
+    if let PropagationStatus::Failed { since, .. } = status {
+        lines.push(format!(
+            "Not propagating since {}",
+            since.format("%Y-%m-%d %H:%M:%S UTC")
+        ));
+    }
+
+    text.0 = lines.join("\n");
+    *visibility = Visibility::Inherited;
+}
+
+/// SATCAT details for the info panel.
+fn metadata_lines(metadata: &SatelliteMetadata) -> Vec<String> {
+    let mut lines = vec![
+        format!("Type: {}", metadata.object_type.label()),
+        format!("Owner: {}", metadata.owner),
+    ];
+    if let Some(date) = metadata.launch_date {
+        lines.push(format!("Launched: {} from {}", date, metadata.launch_site));
+    }
+    match (metadata.rcs, metadata.rcs_size) {
+        (Some(rcs), Some(size)) => lines.push(format!("RCS: {:.4} m² ({})", rcs, size.label())),
+        (None, Some(size)) => lines.push(format!("RCS: {}", size.label())),
+        _ => {}
+    }
+    if let Some(date) = metadata.decay_date {
+        lines.push(format!("Decayed: {}", date));
+    }
+    lines
+}
+
+/// Show the hovered object's name next to the cursor.
+pub fn update_hover_tooltip(
+    selection: Res<Selection>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    names: Query<&Name, With<Debris>>,
+    tooltip: Single<(&mut Text, &mut Node, &mut Visibility), With<HoverTooltip>>,
+) {
+    let (mut text, mut node, mut visibility) = tooltip.into_inner();
+
+    let hovered = selection.hovered.and_then(|e| names.get(e).ok());
+    let (Some(name), Some(cursor)) = (hovered, window.cursor_position()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    text.0 = name.to_string();
+    node.left = Val::Px(cursor.x + 14.0);
+    node.top = Val::Px(cursor.y + 10.0);
+    *visibility = Visibility::Inherited;
+}
@@ -0,0 +1,430 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    math::DVec3,
+    mesh::{Indices, PrimitiveTopology},
+    prelude::*,
+    tasks::{ComputeTaskPool, ParallelSlice, TaskPool},
+};
+use chrono::{DateTime, Utc};
+
+use crate::debris::{Debris, DebrisSat, KM_TO_WORLD, julian_date};
+use crate::earth::SceneFrame;
+use crate::frames::{Geodetic, PolarMotion, teme_to_itrf};
+use crate::loader::TleEntry;
+use crate::propagation::propagate;
+use crate::satcat::SatelliteMetadata;
+
+/// Angular subdivisions of a volume mesh's far face, per side.
+const MESH_DIVISIONS: usize = 24;
+
+/// Shape of a sensor's field of view around its boresight.
+#[derive(Debug, Clone, Copy)]
+pub enum FieldOfView {
+    /// Circular, radians off boresight.
+    Cone { half_angle: f64 },
+    /// Rectangular, radians either side of boresight horizontally (along the
+    /// horizon) and vertically.
+    Fan { half_width: f64, half_height: f64 },
+}
+
+impl FieldOfView {
+    /// `cone:ANGLE` or `fan:WIDTHxHEIGHT`, full angles in degrees.
+    fn parse(arg: &str) -> Result<Self, String> {
+        let angle = |value: &str| {
+            value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|deg| *deg > 0.0 && *deg < 180.0)
+                .map(|deg| deg.to_radians() / 2.0)
+                .ok_or_else(|| format!("bad angle `{}` (degrees, under 180)", value))
+        };
+        match arg.trim().split_once(':') {
+            Some(("cone", value)) => Ok(FieldOfView::Cone {
+                half_angle: angle(value)?,
+            }),
+            Some(("fan", value)) => {
+                let (width, height) = value.split_once('x').ok_or("expected fan:WIDTHxHEIGHT")?;
+                Ok(FieldOfView::Fan {
+                    half_width: angle(width)?,
+                    half_height: angle(height)?,
+                })
+            }
+            _ => Err(format!(
+                "expected cone:ANGLE or fan:WIDTHxHEIGHT, got `{}`",
+                arg
+            )),
+        }
+    }
+}
+
+/// A radar or optical sensor at a ground site.
+#[derive(Debug, Clone)]
+pub struct Sensor {
+    pub name: String,
+    pub site: Geodetic,
+    /// Boresight azimuth, radians clockwise from north.
+    pub azimuth: f64,
+    /// Boresight elevation, radians.
+    pub elevation: f64,
+    pub field_of_view: FieldOfView,
+    pub max_range_km: f64,
+}
+
+/// A sensor's site and boresight axes, in ITRF.
+#[derive(Debug, Clone, Copy)]
+pub struct SensorFrame {
+    /// Site position, km.
+    pub origin: DVec3,
+    pub boresight: DVec3,
+    /// Horizontal, towards increasing azimuth.
+    pub right: DVec3,
+    pub up: DVec3,
+    /// Local vertical at the site.
+    pub zenith: DVec3,
+}
+
+impl Sensor {
+    /// Parse `NAME,LAT,LON,ALT_M,AZ,EL,FOV,RANGE_KM`: site in degrees and
+    /// metres, boresight azimuth and elevation in degrees, field of view as
+    /// `cone:ANGLE` or `fan:WIDTHxHEIGHT` (full angles, degrees) and the
+    /// range limit in km.
+    pub fn parse(arg: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = arg.split(',').map(str::trim).collect();
+        let [name, lat, lon, alt, az, el, fov, range] = parts[..] else {
+            return Err("expected NAME,LAT,LON,ALT_M,AZ,EL,FOV,RANGE_KM".to_string());
+        };
+        let number = |value: &str| {
+            value
+                .parse::<f64>()
+                .map_err(|e| format!("`{}`: {}", value, e))
+        };
+        let (latitude, elevation, max_range_km) = (number(lat)?, number(el)?, number(range)?);
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(format!("latitude {} out of range", latitude));
+        }
+        if !(-90.0..=90.0).contains(&elevation) {
+            return Err(format!("elevation {} out of range", elevation));
+        }
+        if max_range_km <= 0.0 {
+            return Err(format!("range {} must be positive", max_range_km));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            site: Geodetic::from_degrees(latitude, number(lon)?, number(alt)? / 1000.0),
+            azimuth: number(az)?.to_radians(),
+            elevation: elevation.to_radians(),
+            field_of_view: FieldOfView::parse(fov)?,
+            max_range_km,
+        })
+    }
+
+    pub fn frame(&self) -> SensorFrame {
+        let [east, north, zenith] = self.site.enu_axes().map(DVec3::from);
+        let (sin_az, cos_az) = self.azimuth.sin_cos();
+        let (sin_el, cos_el) = self.elevation.sin_cos();
+        let boresight = (east * sin_az + north * cos_az) * cos_el + zenith * sin_el;
+        let right = east * cos_az - north * sin_az;
+        SensorFrame {
+            origin: DVec3::from(self.site.to_itrf()),
+            boresight,
+            right,
+            up: right.cross(boresight),
+            zenith,
+        }
+    }
+
+    /// Whether a target `offset` km from the site (ITRF axes) is inside the
+    /// volume: within range, above the horizon and inside the field of view.
+    pub fn contains(&self, frame: &SensorFrame, offset: DVec3) -> bool {
+        let range = offset.length();
+        let along = offset.dot(frame.boresight);
+        if range > self.max_range_km || along <= 0.0 || offset.dot(frame.zenith) < 0.0 {
+            return false;
+        }
+        match self.field_of_view {
+            FieldOfView::Cone { half_angle } => along >= range * half_angle.cos(),
+            FieldOfView::Fan {
+                half_width,
+                half_height,
+            } => {
+                offset.dot(frame.right).atan2(along).abs() <= half_width
+                    && offset.dot(frame.up).atan2(along).abs() <= half_height
+            }
+        }
+    }
+
+    /// Volume mesh in ITRF axes and world units, apex at the origin: the
+    /// far face is a grid of directions at the range limit, closed off with
+    /// side triangles to the apex.
+    fn volume_mesh(&self) -> Mesh {
+        let frame = self.frame();
+        let n = MESH_DIVISIONS;
+        // Directions row by row; cone rows are rings out from boresight
+        // (and wrap around), fan rows run bottom to top.
+        let (directions, columns, wrap): (Vec<DVec3>, usize, bool) = match self.field_of_view {
+            FieldOfView::Cone { half_angle } => {
+                let directions = (0..=n / 3)
+                    .flat_map(|ring| {
+                        let off = half_angle * ring as f64 / (n / 3) as f64;
+                        (0..n).map(move |i| {
+                            let around = std::f64::consts::TAU * i as f64 / n as f64;
+                            let (sin, cos) = around.sin_cos();
+                            frame.boresight * off.cos()
+                                + (frame.right * cos + frame.up * sin) * off.sin()
+                        })
+                    })
+                    .collect();
+                (directions, n, true)
+            }
+            FieldOfView::Fan {
+                half_width,
+                half_height,
+            } => {
+                let steps = |half: f64| {
+                    (0..=n).map(move |i| (-half + 2.0 * half * i as f64 / n as f64).tan())
+                };
+                let directions = steps(half_height)
+                    .flat_map(|v| {
+                        steps(half_width).map(move |h| {
+                            (frame.boresight + frame.right * h + frame.up * v).normalize()
+                        })
+                    })
+                    .collect();
+                (directions, n + 1, false)
+            }
+        };
+        let rows = directions.len() / columns;
+        let range = self.max_range_km * KM_TO_WORLD as f64;
+
+        let mut positions = vec![[0.0; 3]];
+        positions.extend(directions.iter().map(|d| (*d * range).as_vec3().to_array()));
+        let at = |row: usize, column: usize| (1 + row * columns + column % columns) as u32;
+        let last_column = if wrap { columns } else { columns - 1 };
+
+        let mut indices = Vec::new();
+        for row in 0..rows - 1 {
+            for column in 0..last_column {
+                let (a, b) = (at(row, column), at(row, column + 1));
+                let (c, d) = (at(row + 1, column), at(row + 1, column + 1));
+                indices.extend([a, b, d, a, d, c]);
+            }
+        }
+        // Sides: the outer ring of a cone, the perimeter of a fan.
+        let mut rim: Vec<u32> = if wrap {
+            (0..columns).map(|column| at(rows - 1, column)).collect()
+        } else {
+            let bottom = (0..columns).map(|column| at(0, column));
+            let right = (1..rows).map(|row| at(row, columns - 1));
+            let top = (0..columns - 1).rev().map(|column| at(rows - 1, column));
+            let left = (1..rows - 1).rev().map(|row| at(row, 0));
+            bottom.chain(right).chain(top).chain(left).collect()
+        };
+        rim.push(rim[0]);
+        for edge in rim.windows(2) {
+            indices.extend([0, edge[0], edge[1]]);
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_indices(Indices::U32(indices))
+        .with_computed_normals()
+    }
+}
+
+/// Sensors given on the command line.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct Sensors(pub Vec<Sensor>);
+
+/// A sensor's volume in the scene; the index into `Sensors`.
+#[derive(Component)]
+pub struct SensorVolume(pub usize);
+
+/// Marker for the live sensor count panel.
+#[derive(Component)]
+pub struct SensorPanel;
+
+/// Spawn a translucent volume per sensor and the count panel.
+pub fn setup_sensors(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    sensors: Res<Sensors>,
+) {
+    if sensors.0.is_empty() {
+        return;
+    }
+    let material = materials.add(StandardMaterial {
+        base_color: Color::srgba(0.3, 0.7, 1.0, 0.12),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+    for (i, sensor) in sensors.0.iter().enumerate() {
+        commands.spawn((
+            Name::new(format!("Sensor {}", sensor.name)),
+            SensorVolume(i),
+            Mesh3d(meshes.add(sensor.volume_mesh())),
+            MeshMaterial3d(material.clone()),
+            Transform::default(),
+            GlobalTransform::default(),
+        ));
+    }
+
+    commands.spawn((
+        Name::new("Sensor Panel"),
+        SensorPanel,
+        Text::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(64.0),
+            left: Val::Percent(35.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Interaction::default(),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    ));
+}
+
+/// Keep the volumes on their sites as the Earth turns.
+pub fn place_sensor_volumes(
+    scene: SceneFrame,
+    sensors: Res<Sensors>,
+    mut volumes: Query<(&SensorVolume, &mut Transform)>,
+) {
+    let rotation = scene.itrf_to_display();
+    for (volume, mut transform) in &mut volumes {
+        let Some(sensor) = sensors.0.get(volume.0) else {
+            continue;
+        };
+        let site = DVec3::from(sensor.site.to_itrf()).as_vec3() * KM_TO_WORLD;
+        transform.translation = rotation * site;
+        transform.rotation = rotation;
+    }
+}
+
+/// Count the shown objects inside each sensor's volume now.
+pub fn update_sensor_panel(
+    scene: SceneFrame,
+    sensors: Res<Sensors>,
+    debris: Query<(&GlobalTransform, &InheritedVisibility), With<Debris>>,
+    panel: Option<Single<&mut Text, With<SensorPanel>>>,
+) {
+    let Some(mut text) = panel else {
+        return;
+    };
+    let to_itrf = scene.itrf_to_display().inverse();
+
+    let mut lines = vec!["Objects in sensor view:".to_string()];
+    for sensor in &sensors.0 {
+        let frame = sensor.frame();
+        let count = debris
+            .iter()
+            .filter(|(_, visibility)| visibility.get())
+            .filter(|(transform, _)| {
+                let itrf = (to_itrf * transform.translation()).as_dvec3() / KM_TO_WORLD as f64;
+                sensor.contains(&frame, itrf - frame.origin)
+            })
+            .count();
+        lines.push(format!("{}: {}", sensor.name, count));
+    }
+    text.0 = lines.join("\n");
+}
+
+/// Headless coverage report: sample `entries` every `step_secs` over the
+/// window from `start` and print, per sensor, how many unique objects were
+/// inside its volume at least once and how many samples were (one object
+/// seen at two samples counts twice), plus a row for all sensors together.
+/// Objects crossing a volume between two samples are missed, so thin fans
+/// need a short step. Returns the number of unique objects seen.
+pub fn run_coverage(
+    entries: Vec<(TleEntry, Option<SatelliteMetadata>)>,
+    sensors: &Sensors,
+    start: DateTime<Utc>,
+    window_days: f64,
+    step_secs: f64,
+    polar_motion: PolarMotion,
+) -> Result<usize, String> {
+    ComputeTaskPool::get_or_init(TaskPool::default);
+    let frames: Vec<SensorFrame> = sensors.0.iter().map(Sensor::frame).collect();
+    // Nothing with a perigee above this can come into range.
+    let reach_km = sensors
+        .0
+        .iter()
+        .map(|s| s.site.altitude_km + s.max_range_km)
+        .fold(0.0, f64::max);
+    let sats: Vec<DebrisSat> = entries
+        .into_iter()
+        .map(|(entry, _)| DebrisSat {
+            name: entry.name,
+            elements: entry.elements,
+            satrec: entry.satrec,
+        })
+        .filter(|sat| sat.elements.perigee_altitude_km() < reach_km)
+        .collect();
+
+    let start_jd = julian_date(start);
+    let step_days = step_secs / 86_400.0;
+    let steps = (window_days / step_days).ceil() as usize;
+    // Samples each object spent inside each sensor.
+    let samples: Vec<Vec<usize>> = sats
+        .par_splat_map(ComputeTaskPool::get(), None, |_, chunk| {
+            chunk
+                .iter()
+                .map(|sat| {
+                    let mut sat = sat.clone();
+                    let mut inside = vec![0; sensors.0.len()];
+                    for step in 0..=steps {
+                        let jd_full = start_jd + step as f64 * step_days;
+                        let jd = jd_full.floor();
+                        let Ok(state) = propagate(&mut sat, jd, jd_full - jd) else {
+                            continue;
+                        };
+                        let r = DVec3::from(teme_to_itrf(state.r, jd, jd_full - jd, polar_motion));
+                        for (i, (sensor, frame)) in sensors.0.iter().zip(&frames).enumerate() {
+                            if sensor.contains(frame, r - frame.origin) {
+                                inside[i] += 1;
+                            }
+                        }
+                    }
+                    inside
+                })
+                .collect::<Vec<_>>()
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let mut writer = csv::Writer::from_writer(std::io::stdout().lock());
+    writer
+        .write_record(["SENSOR", "UNIQUE_OBJECTS", "OBJECT_SAMPLES"])
+        .map_err(|e| e.to_string())?;
+    for (i, sensor) in sensors.0.iter().enumerate() {
+        let unique = samples.iter().filter(|inside| inside[i] > 0).count();
+        let total: usize = samples.iter().map(|inside| inside[i]).sum();
+        writer
+            .write_record([sensor.name.clone(), unique.to_string(), total.to_string()])
+            .map_err(|e| e.to_string())?;
+    }
+    let unique = samples
+        .iter()
+        .filter(|inside| inside.iter().any(|n| *n > 0))
+        .count();
+    let total: usize = samples.iter().flatten().sum();
+    writer
+        .write_record(["ALL".to_string(), unique.to_string(), total.to_string()])
+        .map_err(|e| e.to_string())?;
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(unique)
+}
@@ -0,0 +1,527 @@
+use bevy::{
+    prelude::*,
+    tasks::{
+        AsyncComputeTaskPool, ComputeTaskPool, ParallelSlice, Task, TaskPool, futures::check_ready,
+    },
+};
+use chrono::{DateTime, Utc};
+
+use crate::debris::{
+    Debris, DebrisField, DebrisSat, KM_TO_WORLD, SimulationTime, datetime_from_jd, julian_date,
+};
+use crate::earth::{EarthRotation, SceneFrame};
+use crate::frames::{Geodetic, LookAngles, PolarMotion, teme_to_itrf};
+use crate::loader::TleEntry;
+use crate::propagation::{golden_section_min, propagate};
+use crate::satcat::SatelliteMetadata;
+use crate::selection::Selection;
+
+/// Coarse step of the pass search, seconds. Passes shorter than this can be
+/// missed.
+const PASS_STEP_SECS: f64 = 30.0;
+/// AOS / LOS / TCA tolerance, seconds.
+const PASS_TOLERANCE_SECS: f64 = 0.1;
+/// How far ahead passes are predicted for the selected object, days.
+const LOOKAHEAD_DAYS: f64 = 1.0;
+/// Sim time after which the selected object's passes are re-predicted, days.
+const REPREDICT_DAYS: f64 = 1.0 / 24.0;
+/// Upcoming passes listed per station in the pass panel.
+const PANEL_PASSES: usize = 3;
+/// Size of a station marker, world units.
+const MARKER_RADIUS: f32 = 0.012;
+
+/// A ground site that can observe objects above its elevation mask.
+#[derive(Debug, Clone)]
+pub struct GroundStation {
+    pub name: String,
+    pub site: Geodetic,
+    /// Minimum elevation, radians.
+    pub min_elevation: f64,
+}
+
+impl GroundStation {
+    /// Parse `NAME,LAT,LON[,ALT_M[,MIN_EL]]`: degrees north / east, metres
+    /// above the ellipsoid (default 0) and the elevation mask in degrees
+    /// (default 10).
+    pub fn parse(arg: &str) -> Result<Self, String> {
+        let mut parts = arg.split(',').map(str::trim);
+        let name = parts
+            .next()
+            .filter(|n| !n.is_empty())
+            .ok_or("missing name")?;
+        let numbers = parts
+            .map(|p| p.parse::<f64>().map_err(|e| format!("`{}`: {}", p, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (latitude, longitude, altitude_m, min_elevation) = match numbers[..] {
+            [lat, lon] => (lat, lon, 0.0, 10.0),
+            [lat, lon, alt] => (lat, lon, alt, 10.0),
+            [lat, lon, alt, mask] => (lat, lon, alt, mask),
+            _ => return Err("expected NAME,LAT,LON[,ALT_M[,MIN_EL]]".to_string()),
+        };
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(format!("latitude {} out of range", latitude));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            site: Geodetic::from_degrees(latitude, longitude, altitude_m / 1000.0),
+            min_elevation: min_elevation.to_radians(),
+        })
+    }
+
+    /// Where `sat` appears from the station at `jd_full`, or `None` if it
+    /// can't be propagated there.
+    pub fn look_at(
+        &self,
+        sat: &mut DebrisSat,
+        jd_full: f64,
+        polar_motion: PolarMotion,
+    ) -> Option<LookAngles> {
+        let jd = jd_full.floor();
+        let fr = jd_full - jd;
+        let state = propagate(sat, jd, fr).ok()?;
+        let r = teme_to_itrf(state.r, jd, fr, polar_motion);
+        Some(LookAngles::from_itrf(self.site, r))
+    }
+
+    fn sees(&self, sat: &mut DebrisSat, jd_full: f64, polar_motion: PolarMotion) -> bool {
+        self.look_at(sat, jd_full, polar_motion)
+            .is_some_and(|look| look.elevation >= self.min_elevation)
+    }
+}
+
+/// Stations given on the command line.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct GroundStations(pub Vec<GroundStation>);
+
+/// Look angles at one moment of a pass.
+#[derive(Debug, Clone, Copy)]
+pub struct PassSample {
+    /// Full JD.
+    pub jd: f64,
+    pub look: LookAngles,
+}
+
+impl PassSample {
+    pub fn time(&self) -> DateTime<Utc> {
+        datetime_from_jd(self.jd, 0.0)
+    }
+}
+
+/// One pass of an object above a station's elevation mask.
+#[derive(Debug, Clone)]
+pub struct Pass {
+    /// Acquisition of signal: rising through the mask, or the start of the
+    /// prediction window if the pass is already in progress.
+    pub aos: PassSample,
+    /// Highest point of the pass, which is also (to within seconds) the
+    /// closest approach.
+    pub tca: PassSample,
+    /// Loss of signal, or the end of the window.
+    pub los: PassSample,
+    /// Samples every `PASS_STEP_SECS` from AOS to LOS, including both and
+    /// TCA.
+    pub track: Vec<PassSample>,
+}
+
+/// Passes of `sat` over `station` between `start_jd` and `end_jd` (full JD).
+///
+/// The sky is sampled every `PASS_STEP_SECS`; each rise or set between two
+/// samples is bisected, and the highest point of each pass found with a
+/// golden-section search on the elevation.
+pub fn predict_passes(
+    sat: &mut DebrisSat,
+    station: &GroundStation,
+    start_jd: f64,
+    end_jd: f64,
+    polar_motion: PolarMotion,
+) -> Vec<Pass> {
+    let step = PASS_STEP_SECS / 86_400.0;
+    let tolerance = PASS_TOLERANCE_SECS / 86_400.0;
+    // Time the visibility changes between `lo` and `hi`.
+    let edge = |sat: &mut DebrisSat, mut lo: f64, mut hi: f64| {
+        let visible_at_lo = station.sees(sat, lo, polar_motion);
+        while hi - lo > tolerance {
+            let mid = (lo + hi) / 2.0;
+            if station.sees(sat, mid, polar_motion) == visible_at_lo {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        (lo + hi) / 2.0
+    };
+
+    let mut passes = Vec::new();
+    let mut rise = station
+        .sees(sat, start_jd, polar_motion)
+        .then_some(start_jd);
+    let mut previous = start_jd;
+    while previous < end_jd {
+        let jd = (previous + step).min(end_jd);
+        match (rise, station.sees(sat, jd, polar_motion)) {
+            (None, true) => rise = Some(edge(sat, previous, jd)),
+            (Some(aos), false) => {
+                let los = edge(sat, previous, jd);
+                passes.extend(describe_pass(sat, station, aos, los, polar_motion));
+                rise = None;
+            }
+            _ => {}
+        }
+        previous = jd;
+    }
+    if let Some(aos) = rise {
+        passes.extend(describe_pass(sat, station, aos, end_jd, polar_motion));
+    }
+    passes
+}
+
+fn describe_pass(
+    sat: &mut DebrisSat,
+    station: &GroundStation,
+    aos_jd: f64,
+    los_jd: f64,
+    polar_motion: PolarMotion,
+) -> Option<Pass> {
+    let mut sample = |jd: f64| {
+        station
+            .look_at(sat, jd, polar_motion)
+            .map(|look| PassSample { jd, look })
+    };
+    let tca_jd = golden_section_min(
+        |jd| sample(jd).map_or(f64::INFINITY, |s| -s.look.elevation),
+        aos_jd,
+        los_jd,
+        PASS_TOLERANCE_SECS / 86_400.0,
+    );
+    let (aos, tca, los) = (sample(aos_jd)?, sample(tca_jd)?, sample(los_jd)?);
+
+    let step = PASS_STEP_SECS / 86_400.0;
+    let mut track = vec![aos, tca, los];
+    let mut jd = aos_jd + step;
+    while jd < los_jd {
+        track.extend(sample(jd));
+        jd += step;
+    }
+    track.sort_by(|a, b| a.jd.total_cmp(&b.jd));
+    Some(Pass {
+        aos,
+        tca,
+        los,
+        track,
+    })
+}
+
+/// Headless pass prediction: print every pass of `entries` over `stations`
+/// in the window from `start` as CSV, one row per pass or, with `track`,
+/// one row per pass sample. Returns the number of passes.
+pub fn run_pass_prediction(
+    entries: Vec<(TleEntry, Option<SatelliteMetadata>)>,
+    stations: &GroundStations,
+    start: DateTime<Utc>,
+    window_days: f64,
+    polar_motion: PolarMotion,
+    track: bool,
+) -> Result<usize, String> {
+    ComputeTaskPool::get_or_init(TaskPool::default);
+    let objects: Vec<(DebrisSat, String)> = entries
+        .into_iter()
+        .map(|(entry, metadata)| {
+            let name = entry
+                .name
+                .clone()
+                .or_else(|| metadata.map(|m| m.name))
+                .unwrap_or_default();
+            let sat = DebrisSat {
+                name: entry.name,
+                elements: entry.elements,
+                satrec: entry.satrec,
+            };
+            (sat, name)
+        })
+        .collect();
+
+    let start_jd = julian_date(start);
+    let end_jd = start_jd + window_days;
+    let mut passes: Vec<(usize, usize, Pass)> = objects
+        .par_splat_map(ComputeTaskPool::get(), None, |offset, chunk| {
+            let mut found = Vec::new();
+            for (i, (sat, _)) in chunk.iter().enumerate() {
+                let mut sat = sat.clone();
+                for (s, station) in stations.0.iter().enumerate() {
+                    for pass in predict_passes(&mut sat, station, start_jd, end_jd, polar_motion) {
+                        found.push((offset + i, s, pass));
+                    }
+                }
+            }
+            found
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+    passes.sort_by(|a, b| a.2.aos.jd.total_cmp(&b.2.aos.jd));
+
+    let mut writer = csv::Writer::from_writer(std::io::stdout().lock());
+    let time = |sample: &PassSample| sample.time().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+    let degrees = |radians: f64| format!("{:.2}", radians.to_degrees());
+    let header: &[&str] = if track {
+        &[
+            "STATION", "NORAD", "NAME", "AOS_UTC", "TIME_UTC", "AZ_DEG", "EL_DEG", "RANGE_KM",
+        ]
+    } else {
+        &[
+            "STATION",
+            "NORAD",
+            "NAME",
+            "AOS_UTC",
+            "TCA_UTC",
+            "LOS_UTC",
+            "MAX_EL_DEG",
+            "AOS_AZ_DEG",
+            "TCA_AZ_DEG",
+            "LOS_AZ_DEG",
+            "TCA_RANGE_KM",
+        ]
+    };
+    writer.write_record(header).map_err(|e| e.to_string())?;
+    for (object, station, pass) in &passes {
+        let (sat, name) = &objects[*object];
+        let prefix = [
+            stations.0[*station].name.clone(),
+            sat.elements.catalog_number.to_string(),
+            name.clone(),
+            time(&pass.aos),
+        ];
+        let rows = if track {
+            pass.track
+                .iter()
+                .map(|sample| {
+                    vec![
+                        time(sample),
+                        degrees(sample.look.azimuth),
+                        degrees(sample.look.elevation),
+                        format!("{:.3}", sample.look.range_km),
+                    ]
+                })
+                .collect()
+        } else {
+            vec![vec![
+                time(&pass.tca),
+                time(&pass.los),
+                degrees(pass.tca.look.elevation),
+                degrees(pass.aos.look.azimuth),
+                degrees(pass.tca.look.azimuth),
+                degrees(pass.los.look.azimuth),
+                format!("{:.3}", pass.tca.look.range_km),
+            ]]
+        };
+        for row in rows {
+            writer
+                .write_record(prefix.iter().cloned().chain(row))
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(passes.len())
+}
+
+/// Upcoming passes of the selected object over each station, and the
+/// prediction in progress.
+#[derive(Default, Resource)]
+pub struct SelectedPasses {
+    pub entity: Option<Entity>,
+    /// `DebrisField::generation` the passes (or the running task) are for.
+    pub generation: u64,
+    /// Full JD the prediction starts at.
+    pub from_jd: f64,
+    /// Per station, in `GroundStations` order. Empty until the first
+    /// prediction for the object finishes.
+    pub passes: Vec<Vec<Pass>>,
+    /// Prediction running in the background.
+    task: Option<Task<Vec<Vec<Pass>>>>,
+}
+
+/// Marker for the pass panel.
+#[derive(Component)]
+pub struct PassPanel;
+
+/// Draw each station as a marker on the Earth, with a line to every shown
+/// object above its elevation mask.
+pub fn draw_ground_stations(
+    scene: SceneFrame,
+    stations: Res<GroundStations>,
+    debris: Query<(&GlobalTransform, &InheritedVisibility), With<Debris>>,
+    mut gizmos: Gizmos,
+) {
+    let rotation = scene.itrf_to_display();
+    let to_display = |v: [f64; 3]| rotation * Vec3::new(v[0] as f32, v[1] as f32, v[2] as f32);
+
+    for station in &stations.0 {
+        let position = to_display(station.site.to_itrf()) * KM_TO_WORLD;
+        let up = to_display(station.site.enu_axes()[2]);
+        let min_sin = station.min_elevation.sin() as f32;
+        gizmos.sphere(
+            Isometry3d::from_translation(position),
+            MARKER_RADIUS,
+            Color::srgb(0.3, 1.0, 0.4),
+        );
+
+        for (transform, visibility) in &debris {
+            if !visibility.get() {
+                continue;
+            }
+            let target = transform.translation();
+            if (target - position).normalize_or_zero().dot(up) >= min_sin {
+                gizmos.line(position, target, Color::srgba(0.3, 1.0, 0.4, 0.35));
+            }
+        }
+    }
+}
+
+/// Predict the selected object's passes over the next `LOOKAHEAD_DAYS` in
+/// the background when the selection or catalog changes or the clock moves
+/// on, and pick up finished predictions.
+pub fn predict_selected_passes(
+    selection: Res<Selection>,
+    sim_time: Res<SimulationTime>,
+    stations: Res<GroundStations>,
+    earth_rotation: Res<EarthRotation>,
+    debris_field: Res<DebrisField>,
+    debris: Query<&Debris>,
+    mut selected: ResMut<SelectedPasses>,
+) {
+    let now = sim_time.jd + sim_time.fr;
+    let same_object =
+        selected.entity == selection.selected && selected.generation == debris_field.generation;
+    // Both ways, so scrubbing the timeline back re-predicts too.
+    let current = same_object && (now - selected.from_jd).abs() < REPREDICT_DAYS;
+    if !current && !stations.0.is_empty() {
+        if !same_object {
+            selected.passes.clear();
+        }
+        let sat = selection
+            .selected
+            .and_then(|entity| debris.get(entity).ok())
+            .and_then(|d| debris_field.sats.get(d.sat_index))
+            .cloned();
+        let stations = stations.0.clone();
+        let polar_motion = earth_rotation.polar_motion;
+        selected.entity = selection.selected;
+        selected.generation = debris_field.generation;
+        selected.from_jd = now;
+        // Replacing a running task drops, and so cancels, it.
+        selected.task = sat.map(|mut sat| {
+            AsyncComputeTaskPool::get().spawn(async move {
+                stations
+                    .iter()
+                    .map(|station| {
+                        predict_passes(&mut sat, station, now, now + LOOKAHEAD_DAYS, polar_motion)
+                    })
+                    .collect()
+            })
+        });
+    }
+
+    // Polling mustn't count as a change, or the panel rebuilds every frame.
+    let finished = selected
+        .bypass_change_detection()
+        .task
+        .as_mut()
+        .and_then(check_ready);
+    if let Some(passes) = finished {
+        selected.task = None;
+        selected.passes = passes;
+    }
+}
+
+/// Spawn the (initially hidden) pass panel under the filter bar.
+pub fn setup_pass_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Pass Panel"),
+        PassPanel,
+        Text::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(72.0),
+            left: Val::Percent(30.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Interaction::default(),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Visibility::Hidden,
+    ));
+}
+
+/// List where the selected object is from each station now and its next
+/// passes.
+pub fn update_pass_panel(
+    selected: Res<SelectedPasses>,
+    stations: Res<GroundStations>,
+    sim_time: Res<SimulationTime>,
+    earth_rotation: Res<EarthRotation>,
+    mut debris_field: ResMut<DebrisField>,
+    debris: Query<&Debris>,
+    panel: Single<(&mut Text, &mut Visibility), With<PassPanel>>,
+) {
+    let (mut text, mut visibility) = panel.into_inner();
+    let sat = selected
+        .entity
+        .and_then(|entity| debris.get(entity).ok())
+        .and_then(|d| debris_field.sats.get_mut(d.sat_index));
+    let Some(sat) = sat.filter(|_| !stations.0.is_empty()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    let now = sim_time.jd + sim_time.fr;
+    let mut lines = Vec::new();
+    for (i, station) in stations.0.iter().enumerate() {
+        let here = match station.look_at(sat, now, earth_rotation.polar_motion) {
+            Some(look) => format!(
+                "az {:.1}°  el {:.1}°  range {:.0} km{}",
+                look.azimuth.to_degrees(),
+                look.elevation.to_degrees(),
+                look.range_km,
+                if look.elevation >= station.min_elevation {
+                    "  (in view)"
+                } else {
+                    ""
+                }
+            ),
+            None => "not propagating".to_string(),
+        };
+        lines.push(format!("{}: {}", station.name, here));
+
+        let Some(passes) = selected.passes.get(i) else {
+            lines.push("  predicting passes...".to_string());
+            continue;
+        };
+        let upcoming: Vec<_> = passes
+            .iter()
+            .filter(|pass| pass.los.jd > now)
+            .take(PANEL_PASSES)
+            .collect();
+        if upcoming.is_empty() {
+            lines.push(format!(
+                "  no passes in the next {} h",
+                LOOKAHEAD_DAYS * 24.0
+            ));
+        }
+        for pass in upcoming {
+            lines.push(format!(
+                "  AOS {}  TCA {}  LOS {}  max el {:.1}°",
+                pass.aos.time().format("%m-%d %H:%M:%S"),
+                pass.tca.time().format("%H:%M:%S"),
+                pass.los.time().format("%H:%M:%S"),
+                pass.tca.look.elevation.to_degrees()
+            ));
+        }
+    }
+    text.0 = lines.join("\n");
+    *visibility = Visibility::Inherited;
+}
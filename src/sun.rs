@@ -0,0 +1,146 @@
+use bevy::{math::DVec3, prelude::*};
+
+use crate::debris::{Debris, EARTH_RADIUS_KM, SimulationTime, teme_to_world};
+use crate::earth::{EarthRotation, SceneFrame};
+use crate::propagation::DebrisStates;
+
+pub const AU_KM: f64 = 149_597_870.7;
+pub const SUN_RADIUS_KM: f64 = 696_000.0;
+/// Sunlight illuminance, lux. Lower than the real ~100,000 so the lit
+/// hemisphere shows the texture at about its own color under the camera's
+/// default exposure.
+const SUNLIGHT_LUX: f32 = 3_000.0;
+
+/// Geocentric position of the Sun, km, at a full JD. The low-precision
+/// series from the Astronomical Almanac (Vallado's `sun`), good to about
+/// 0.01°. It is in mean-of-date axes, which differ from TEME by nutation
+/// in longitude only: well under a Sun diameter.
+pub fn sun_position(jd_full: f64) -> [f64; 3] {
+    let t = (jd_full - 2_451_545.0) / 36_525.0;
+    let mean_longitude = 280.460 + 36_000.771 * t;
+    let mean_anomaly = (357.529_109_2 + 35_999.050_34 * t).to_radians();
+    let longitude = (mean_longitude
+        + 1.914_666_471 * mean_anomaly.sin()
+        + 0.019_994_643 * (2.0 * mean_anomaly).sin())
+    .to_radians();
+    let distance_au = 1.000_140_612
+        - 0.016_708_617 * mean_anomaly.cos()
+        - 0.000_139_589 * (2.0 * mean_anomaly).cos();
+    let obliquity = (23.439_291 - 0.013_004_2 * t).to_radians();
+
+    let r = distance_au * AU_KM;
+    let (sin_lon, cos_lon) = longitude.sin_cos();
+    [
+        r * cos_lon,
+        r * obliquity.cos() * sin_lon,
+        r * obliquity.sin() * sin_lon,
+    ]
+}
+
+/// Whether an object is in the Earth's shadow.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Illumination {
+    #[default]
+    Sunlit,
+    /// Part of the solar disc is hidden by the Earth.
+    Penumbra,
+    /// The Sun is completely hidden.
+    Umbra,
+}
+
+impl Illumination {
+    pub const ALL: [Illumination; 3] = [
+        Illumination::Sunlit,
+        Illumination::Penumbra,
+        Illumination::Umbra,
+    ];
+
+    /// Conical shadow model: compare the apparent radii of the Sun and the
+    /// Earth seen from the object (TEME, km) with their angular separation.
+    pub fn of(r: [f64; 3], sun: [f64; 3]) -> Self {
+        let r = DVec3::from(r);
+        let to_sun = DVec3::from(sun) - r;
+        let sun_radius = (SUN_RADIUS_KM / to_sun.length()).asin();
+        let earth_radius = (EARTH_RADIUS_KM / r.length()).min(1.0).asin();
+        let separation = (-r).angle_between(to_sun);
+
+        if separation >= sun_radius + earth_radius {
+            Illumination::Sunlit
+        } else if separation <= earth_radius - sun_radius {
+            Illumination::Umbra
+        } else {
+            Illumination::Penumbra
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Illumination::Sunlit => "Sunlit",
+            Illumination::Penumbra => "Penumbra",
+            Illumination::Umbra => "Umbra",
+        }
+    }
+}
+
+/// The Sun at the current sim time.
+#[derive(Debug, Default, Resource)]
+pub struct Sun {
+    /// TEME position, km.
+    pub position: [f64; 3],
+    /// ITRF position, km.
+    pub itrf: [f64; 3],
+}
+
+/// Marker for the directional light standing in for the Sun.
+#[derive(Component)]
+pub struct Sunlight;
+
+pub fn setup_sunlight(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Sunlight"),
+        Sunlight,
+        DirectionalLight {
+            illuminance: SUNLIGHT_LUX,
+            ..default()
+        },
+        Transform::default(),
+    ));
+}
+
+pub fn update_sun(
+    sim_time: Res<SimulationTime>,
+    earth_rotation: Res<EarthRotation>,
+    mut sun: ResMut<Sun>,
+) {
+    let (jd, fr) = (sim_time.jd, sim_time.fr);
+    sun.position = sun_position(jd + fr);
+    sun.itrf = earth_rotation.teme_to_itrf(sun.position, jd, fr);
+}
+
+/// Shine the light from the Sun's direction in the displayed frame.
+pub fn orient_sunlight(
+    sun: Res<Sun>,
+    scene: SceneFrame,
+    mut light: Single<&mut Transform, With<Sunlight>>,
+) {
+    let toward_sun = scene.inertial_to_display() * teme_to_world(&sun.position).normalize();
+    // The Sun stays within 23.5° of the equator, so the pole is a safe up.
+    light.look_to(-toward_sun, Vec3::Y);
+}
+
+/// Work out which objects are in the Earth's shadow at the sim time.
+pub fn update_illumination(
+    sim_time: Res<SimulationTime>,
+    sun: Res<Sun>,
+    states: Res<DebrisStates>,
+    mut debris: Query<(&Debris, &mut Illumination)>,
+) {
+    let (jd, fr) = (sim_time.jd, sim_time.fr);
+    debris
+        .par_iter_mut()
+        .for_each(|(debris, mut illumination)| {
+            if let Some(Ok(r)) = states.position(debris.sat_index, jd, fr) {
+                illumination.set_if_neq(Illumination::of(r, sun.position));
+            }
+        });
+}
@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+use chrono::{DateTime, Utc};
+
+use crate::debris::{DebrisAssets, SimulationConfig};
+use crate::elements::MeanElements;
+use crate::loader::{LoadSummary, TleCatalog, TleEntry};
+
+/// Number of random LEO objects to add to the debris field, for load
+/// testing. Zero adds none.
+#[derive(Debug, Default, Resource)]
+pub struct SyntheticPopulation {
+    pub count: usize,
+}
+
+/// SplitMix64, so populations are reproducible without pulling in `rand`.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn range(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.next_f64()
+    }
+}
+
+/// A catalog of `count` random near-circular LEO orbits with their epoch at
+/// `epoch`. The same count always gives the same population.
+pub fn synthetic_catalog(count: usize, epoch: DateTime<Utc>) -> TleCatalog {
+    let mut rng = SplitMix64(count as u64);
+    let entries = (0..count)
+        .map(|i| {
+            let elements = MeanElements {
+                catalog_number: 900_000_000 + i as u32,
+                object_id: String::new(),
+                classification: 'U',
+                epoch,
+                // Roughly 300 to 2,000 km altitude.
+                mean_motion: rng.range(12.0, 15.8),
+                eccentricity: rng.range(0.0, 0.01),
+                inclination: rng.range(0.0, 100.0),
+                raan: rng.range(0.0, 360.0),
+                arg_of_pericenter: rng.range(0.0, 360.0),
+                mean_anomaly: rng.range(0.0, 360.0),
+                bstar: 0.0,
+                mean_motion_dot: 0.0,
+                mean_motion_ddot: 0.0,
+                element_set_no: 999,
+                rev_at_epoch: 0,
+            };
+            TleEntry {
+                name: Some(format!("SYNTHETIC {}", i)),
+                satrec: elements.to_satrec(),
+                elements,
+            }
+        })
+        .collect();
+
+    TleCatalog {
+        entries,
+        summary: LoadSummary {
+            loaded: count,
+            ..default()
+        },
+    }
+}
+
+/// Add the synthetic population (if any) as an extra in-memory catalog.
+pub fn add_synthetic_population(
+    population: Res<SyntheticPopulation>,
+    config: Res<SimulationConfig>,
+    mut catalogs: ResMut<Assets<TleCatalog>>,
+    mut debris_assets: ResMut<DebrisAssets>,
+) {
+    if population.count == 0 {
+        return;
+    }
+    let epoch = config.start_epoch.unwrap_or_else(Utc::now);
+    let catalog = synthetic_catalog(population.count, epoch);
+    info!("Generated {} synthetic objects", population.count);
+    debris_assets.catalogs.push(catalogs.add(catalog));
+}
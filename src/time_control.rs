@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use chrono::Utc;
+
+use crate::debris::SimulationTime;
+use crate::earth::DisplayFrame;
+
+/// Multipliers stepped through with `,` / `.`.
+const TIME_SCALE_STEPS: [f64; 14] = [
+    -1000.0, -300.0, -100.0, -30.0, -10.0, -3.0, -1.0, 1.0, 3.0, 10.0, 30.0, 100.0, 300.0, 1000.0,
+];
+
+/// Real time covered by one single-frame step (one frame at 60 FPS).
+const FRAME_STEP_SECS: f64 = 1.0 / 60.0;
+
+/// Marker for the on-screen sim clock text.
+#[derive(Component)]
+pub struct SimulationClock;
+
+/// Advance the sim clock by this frame's real delta times the time scale.
+pub fn advance_simulation_time(time: Res<Time>, mut sim_time: ResMut<SimulationTime>) {
+    if sim_time.paused {
+        return;
+    }
+    let delta = time.delta_secs_f64() * sim_time.time_scale;
+    sim_time.advance_secs(delta);
+}
+
+/// Keyboard time controls:
+/// Space -> play / pause
+/// `.` / `,` -> next faster / slower multiplier
+/// R -> reverse direction
+/// Right / Left arrow -> pause and step one frame forward / back
+/// N -> jump to now, Home -> jump to the start epoch
+pub fn time_control_input(keys: Res<ButtonInput<KeyCode>>, mut sim_time: ResMut<SimulationTime>) {
+    if keys.just_pressed(KeyCode::Space) {
+        sim_time.paused = !sim_time.paused;
+    }
+
+    if keys.just_pressed(KeyCode::Period) {
+        let scale = sim_time.time_scale;
+        if let Some(&next) = TIME_SCALE_STEPS.iter().find(|&&s| s > scale) {
+            sim_time.time_scale = next;
+        }
+    }
+    if keys.just_pressed(KeyCode::Comma) {
+        let scale = sim_time.time_scale;
+        if let Some(&prev) = TIME_SCALE_STEPS.iter().rev().find(|&&s| s < scale) {
+            sim_time.time_scale = prev;
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyR) {
+        sim_time.time_scale = -sim_time.time_scale;
+    }
+
+    // Steps follow the magnitude of the current multiplier, so a step at
+    // 1000x covers as much sim time as a frame of playback would.
+    let step = FRAME_STEP_SECS * sim_time.time_scale.abs();
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        sim_time.paused = true;
+        sim_time.advance_secs(step);
+    }
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        sim_time.paused = true;
+        sim_time.advance_secs(-step);
+    }
+
+    if keys.just_pressed(KeyCode::KeyN) {
+        sim_time.set_utc(Utc::now());
+    }
+    if keys.just_pressed(KeyCode::Home) {
+        let start_jd = sim_time.start_jd;
+        sim_time.set_jd(start_jd);
+    }
+}
+
+/// Spawn the sim clock in the top-right corner.
+pub fn setup_clock(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Simulation Clock"),
+        SimulationClock,
+        Text::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            right: Val::Px(12.0),
+            ..default()
+        },
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    ));
+}
+
+/// Show the current UTC sim time, multiplier and (if not inertial) display
+/// frame.
+pub fn update_clock(
+    sim_time: Res<SimulationTime>,
+    frame: Res<DisplayFrame>,
+    mut clock: Single<&mut Text, With<SimulationClock>>,
+) {
+    let state = if sim_time.paused { "  (paused)" } else { "" };
+    let frame = match *frame {
+        DisplayFrame::Inertial => "",
+        DisplayFrame::EarthFixed => "  [Earth-fixed]",
+    };
+    clock.0 = format!(
+        "{}  {}x{}{}",
+        sim_time.utc().format("%Y-%m-%d %H:%M:%S UTC"),
+        sim_time.time_scale,
+        state,
+        frame
+    );
+}
@@ -0,0 +1,195 @@
+use bevy::{prelude::*, ui::RelativeCursorPosition};
+
+use crate::debris::{SimulationTime, datetime_from_jd};
+
+/// Time window covered by the timeline bar, relative to the start epoch.
+#[derive(Debug, Resource)]
+pub struct TimelineSettings {
+    /// Days shown before and after the start epoch.
+    pub span_days: f64,
+}
+
+impl Default for TimelineSettings {
+    fn default() -> Self {
+        Self { span_days: 3.0 }
+    }
+}
+
+impl TimelineSettings {
+    /// Full JD range covered by the bar.
+    fn window(&self, sim_time: &SimulationTime) -> (f64, f64) {
+        (
+            sim_time.start_jd - self.span_days,
+            sim_time.start_jd + self.span_days,
+        )
+    }
+}
+
+/// The draggable bar. Pressing anywhere on it (and dragging, even past its
+/// ends) sets the sim clock.
+#[derive(Component)]
+pub struct TimelineTrack;
+
+/// Marker showing the current sim time on the track.
+#[derive(Component)]
+pub struct TimelineHandle;
+
+/// Which label of the timeline a text node is.
+#[derive(Component)]
+pub enum TimelineLabel {
+    Start,
+    End,
+    /// Offset of the current sim time from the start epoch.
+    Current,
+}
+
+/// Spawn the timeline along the bottom of the window.
+pub fn setup_timeline(mut commands: Commands) {
+    let label_font = TextFont {
+        font_size: 14.0,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            Name::new("Timeline"),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(12.0),
+                left: Val::Px(12.0),
+                right: Val::Px(12.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+        ))
+        .with_children(|timeline| {
+            timeline.spawn((
+                TimelineLabel::Current,
+                Text::new(""),
+                label_font.clone(),
+                TextColor(Color::WHITE),
+            ));
+
+            timeline
+                .spawn(Node {
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn((
+                        TimelineLabel::Start,
+                        Text::new(""),
+                        label_font.clone(),
+                        TextColor(Color::WHITE),
+                    ));
+
+                    row.spawn((
+                        TimelineTrack,
+                        Interaction::default(),
+                        RelativeCursorPosition::default(),
+                        Node {
+                            flex_grow: 1.0,
+                            height: Val::Px(14.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.2)),
+                    ))
+                    .with_children(|track| {
+                        // Start epoch tick in the middle of the window.
+                        track.spawn((
+                            Node {
+                                position_type: PositionType::Absolute,
+                                left: Val::Percent(50.0),
+                                width: Val::Px(1.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.5)),
+                        ));
+                        track.spawn((
+                            TimelineHandle,
+                            Node {
+                                position_type: PositionType::Absolute,
+                                left: Val::Percent(50.0),
+                                width: Val::Px(4.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgb(0.9, 0.2, 0.2)),
+                        ));
+                    });
+
+                    row.spawn((
+                        TimelineLabel::End,
+                        Text::new(""),
+                        label_font,
+                        TextColor(Color::WHITE),
+                    ));
+                });
+        });
+}
+
+/// While the track is pressed, drive the sim clock from the cursor position.
+pub fn drag_timeline(
+    settings: Res<TimelineSettings>,
+    mut sim_time: ResMut<SimulationTime>,
+    track: Single<(&Interaction, &RelativeCursorPosition), With<TimelineTrack>>,
+) {
+    let (interaction, cursor) = track.into_inner();
+    if *interaction != Interaction::Pressed {
+        return;
+    }
+    let Some(normalized) = cursor.normalized else {
+        return;
+    };
+
+    // `normalized` runs -0.5..0.5 across the node.
+    let t = (normalized.x as f64 + 0.5).clamp(0.0, 1.0);
+    let (start, end) = settings.window(&sim_time);
+    sim_time.set_jd(start + t * (end - start));
+}
+
+/// Move the handle and refresh the labels.
+pub fn update_timeline(
+    settings: Res<TimelineSettings>,
+    sim_time: Res<SimulationTime>,
+    mut handle: Single<&mut Node, With<TimelineHandle>>,
+    mut labels: Query<(&TimelineLabel, &mut Text)>,
+) {
+    let (start, end) = settings.window(&sim_time);
+    let jd_full = sim_time.jd + sim_time.fr;
+    let t = ((jd_full - start) / (end - start)).clamp(0.0, 1.0);
+    handle.left = Val::Percent(t as f32 * 100.0);
+
+    for (label, mut text) in &mut labels {
+        text.0 = match label {
+            TimelineLabel::Start => datetime_from_jd(start, 0.0)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            TimelineLabel::End => datetime_from_jd(end, 0.0)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            TimelineLabel::Current => format_offset((jd_full - sim_time.start_jd) * 86_400.0),
+        };
+    }
+}
+
+/// `T+1d 02:03:04` style offset from the start epoch.
+fn format_offset(secs: f64) -> String {
+    let sign = if secs < 0.0 { '-' } else { '+' };
+    let total = secs.abs().round() as u64;
+    let (days, rem) = (total / 86_400, total % 86_400);
+    format!(
+        "T{}{}d {:02}:{:02}:{:02}",
+        sign,
+        days,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
@@ -0,0 +1,476 @@
+//! Optical visibility from ground stations: an object can be seen when the
+//! site is dark enough, the object is sunlit and above the elevation mask,
+//! and it is bright enough. Brightness is estimated from range, phase angle
+//! and an assumed size and albedo.
+
+use bevy::{
+    math::DVec3,
+    prelude::*,
+    tasks::{ComputeTaskPool, ParallelSlice, TaskPool},
+};
+use chrono::{DateTime, Utc};
+
+use crate::debris::{Debris, DebrisField, DebrisSat, KM_TO_WORLD, datetime_from_jd, julian_date};
+use crate::earth::SceneFrame;
+use crate::frames::{LookAngles, PolarMotion, teme_to_itrf};
+use crate::loader::TleEntry;
+use crate::propagation::propagate;
+use crate::satcat::{ObjectType, SatelliteMetadata};
+use crate::stations::{GroundStation, GroundStations, PassSample, predict_passes};
+use crate::sun::{Illumination, Sun, sun_position};
+
+/// Apparent magnitude of the Sun.
+const SUN_MAGNITUDE: f64 = -26.74;
+/// Sampling step within a pass when looking for the visible part, seconds.
+const VISIBLE_STEP_SECS: f64 = 10.0;
+/// Step of the search for dusk and dawn, seconds.
+const TWILIGHT_STEP_SECS: f64 = 300.0;
+/// Dusk / dawn tolerance, seconds.
+const TWILIGHT_TOLERANCE_SECS: f64 = 1.0;
+/// How far ahead the next night is looked for, and the longest night
+/// reported (polar winter), days.
+pub const NIGHT_SEARCH_DAYS: f64 = 1.0;
+/// Brightest objects listed per station in the sky panel.
+const PANEL_OBJECTS: usize = 5;
+
+/// What counts as observable.
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct OpticalSettings {
+    /// Highest Sun elevation at the site that is dark enough, radians.
+    pub twilight: f64,
+    /// Faintest magnitude that can be seen: about 6 by eye under dark skies,
+    /// more with a telescope.
+    pub limiting_magnitude: f64,
+    /// Bond albedo assumed for every object.
+    pub albedo: f64,
+}
+
+/// An object seen from a station.
+#[derive(Debug, Clone, Copy)]
+pub struct Sighting {
+    pub look: LookAngles,
+    /// Estimated visual magnitude.
+    pub magnitude: f64,
+}
+
+/// Assumed diameter of an object, m: that of a disc with its RCS when the
+/// SATCAT has one, else a typical size for its type.
+pub fn diameter_m(name: Option<&str>, metadata: Option<&SatelliteMetadata>) -> f64 {
+    if let Some(rcs) = metadata.and_then(|m| m.rcs).filter(|rcs| *rcs > 0.0) {
+        return 2.0 * (rcs / std::f64::consts::PI).sqrt();
+    }
+    match metadata.map_or_else(|| ObjectType::from_name(name), |m| m.object_type) {
+        ObjectType::Payload => 2.0,
+        ObjectType::RocketBody => 4.0,
+        ObjectType::Debris => 0.3,
+        ObjectType::Unknown => 1.0,
+    }
+}
+
+/// Visual magnitude of a diffusely reflecting (Lambertian) sphere of
+/// `diameter_m` at `range_km` from the observer, with the Sun and observer
+/// `phase` radians apart as seen from the object.
+pub fn magnitude(diameter_m: f64, albedo: f64, range_km: f64, phase: f64) -> f64 {
+    let radius_km = diameter_m / 2000.0;
+    let phase_function =
+        ((std::f64::consts::PI - phase) * phase.cos() + phase.sin()) / std::f64::consts::PI;
+    let flux = 2.0 / 3.0 * albedo * (radius_km / range_km).powi(2) * phase_function;
+    SUN_MAGNITUDE - 2.5 * flux.max(f64::MIN_POSITIVE).log10()
+}
+
+/// Elevation of the Sun (ITRF, km) at a station, radians.
+pub fn sun_elevation(station: &GroundStation, sun_itrf: [f64; 3]) -> f64 {
+    LookAngles::from_itrf(station.site, sun_itrf).elevation
+}
+
+impl OpticalSettings {
+    /// Whether the sky at `station` is dark enough to see satellites.
+    pub fn is_dark(&self, station: &GroundStation, sun_itrf: [f64; 3]) -> bool {
+        sun_elevation(station, sun_itrf) <= self.twilight
+    }
+
+    /// How an object at `r_itrf` would appear from `station`, if it is above
+    /// the mask, sunlit and bright enough. Doesn't check the sky is dark.
+    pub fn sighting(
+        &self,
+        station: &GroundStation,
+        sun_itrf: [f64; 3],
+        r_itrf: [f64; 3],
+        diameter_m: f64,
+    ) -> Option<Sighting> {
+        let look = LookAngles::from_itrf(station.site, r_itrf);
+        if look.elevation < station.min_elevation
+            || Illumination::of(r_itrf, sun_itrf) != Illumination::Sunlit
+        {
+            return None;
+        }
+        let r = DVec3::from(r_itrf);
+        let to_sun = DVec3::from(sun_itrf) - r;
+        let to_site = DVec3::from(station.site.to_itrf()) - r;
+        let magnitude = magnitude(
+            diameter_m,
+            self.albedo,
+            look.range_km,
+            to_sun.angle_between(to_site),
+        );
+        (magnitude <= self.limiting_magnitude).then_some(Sighting { look, magnitude })
+    }
+}
+
+/// The Sun's ITRF position at a full JD, km.
+fn sun_itrf(jd_full: f64, polar_motion: PolarMotion) -> [f64; 3] {
+    let jd = jd_full.floor();
+    teme_to_itrf(sun_position(jd_full), jd, jd_full - jd, polar_motion)
+}
+
+/// The first dark spell at `station` starting within `NIGHT_SEARCH_DAYS` of
+/// `start_jd` (or already under way), as full JDs, cut off
+/// `NIGHT_SEARCH_DAYS` after it begins. `None` when it doesn't get dark.
+pub fn next_night(
+    station: &GroundStation,
+    settings: &OpticalSettings,
+    start_jd: f64,
+    polar_motion: PolarMotion,
+) -> Option<(f64, f64)> {
+    let dark = |jd: f64| settings.is_dark(station, sun_itrf(jd, polar_motion));
+    let step = TWILIGHT_STEP_SECS / 86_400.0;
+    // Time darkness changes between `lo` and `hi`.
+    let edge = |mut lo: f64, mut hi: f64| {
+        let dark_at_lo = dark(lo);
+        while hi - lo > TWILIGHT_TOLERANCE_SECS / 86_400.0 {
+            let mid = (lo + hi) / 2.0;
+            if dark(mid) == dark_at_lo {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        (lo + hi) / 2.0
+    };
+    // First change of darkness after `from`, up to `until`.
+    let next_change = |from: f64, until: f64, now_dark: bool| {
+        let mut previous = from;
+        while previous < until {
+            let jd = (previous + step).min(until);
+            if dark(jd) != now_dark {
+                return Some(edge(previous, jd));
+            }
+            previous = jd;
+        }
+        None
+    };
+
+    let dusk = if dark(start_jd) {
+        start_jd
+    } else {
+        next_change(start_jd, start_jd + NIGHT_SEARCH_DAYS, false)?
+    };
+    let cutoff = dusk + NIGHT_SEARCH_DAYS;
+    let dawn = next_change(dusk, cutoff, true).unwrap_or(cutoff);
+    Some((dusk, dawn))
+}
+
+/// The part of a pass where an object can be seen.
+#[derive(Debug, Clone)]
+pub struct VisiblePass {
+    /// First and last visible samples.
+    pub start: PassSample,
+    pub end: PassSample,
+    /// Highest visible sample.
+    pub highest: PassSample,
+    /// Brightest magnitude over the visible samples.
+    pub brightest: f64,
+}
+
+/// Visible passes of `sat` over `station` between `start_jd` and `end_jd`:
+/// each pass is sampled every `VISIBLE_STEP_SECS` and reported from its
+/// first to its last visible sample.
+pub fn predict_visible_passes(
+    sat: &mut DebrisSat,
+    station: &GroundStation,
+    settings: &OpticalSettings,
+    diameter_m: f64,
+    (start_jd, end_jd): (f64, f64),
+    polar_motion: PolarMotion,
+) -> Vec<VisiblePass> {
+    let step = VISIBLE_STEP_SECS / 86_400.0;
+    let mut visible_passes = Vec::new();
+    for pass in predict_passes(sat, station, start_jd, end_jd, polar_motion) {
+        let mut visible: Option<VisiblePass> = None;
+        let mut jd_full = pass.aos.jd;
+        loop {
+            let jd = jd_full.floor();
+            let sun = sun_itrf(jd_full, polar_motion);
+            let sighting = propagate(sat, jd, jd_full - jd)
+                .ok()
+                .filter(|_| settings.is_dark(station, sun))
+                .and_then(|state| {
+                    let r = teme_to_itrf(state.r, jd, jd_full - jd, polar_motion);
+                    settings.sighting(station, sun, r, diameter_m)
+                });
+            if let Some(sighting) = sighting {
+                let sample = PassSample {
+                    jd: jd_full,
+                    look: sighting.look,
+                };
+                match &mut visible {
+                    None => {
+                        visible = Some(VisiblePass {
+                            start: sample,
+                            end: sample,
+                            highest: sample,
+                            brightest: sighting.magnitude,
+                        })
+                    }
+                    Some(v) => {
+                        v.end = sample;
+                        if sample.look.elevation > v.highest.look.elevation {
+                            v.highest = sample;
+                        }
+                        v.brightest = v.brightest.min(sighting.magnitude);
+                    }
+                }
+            }
+            if jd_full >= pass.los.jd {
+                break;
+            }
+            jd_full = (jd_full + step).min(pass.los.jd);
+        }
+        visible_passes.extend(visible);
+    }
+    visible_passes
+}
+
+/// What [`run_tonight`] found.
+#[derive(Debug)]
+pub struct Tonight {
+    /// Dusk and dawn at each station, in `GroundStations` order; `None`
+    /// where it doesn't get dark within `NIGHT_SEARCH_DAYS`.
+    pub nights: Vec<Option<(DateTime<Utc>, DateTime<Utc>)>>,
+    /// Number of visible passes printed.
+    pub passes: usize,
+}
+
+/// Headless "what can I see tonight": find the next night at each station
+/// from `start` and print every visible pass of `entries` in it as CSV,
+/// sorted by start time.
+pub fn run_tonight(
+    entries: Vec<(TleEntry, Option<SatelliteMetadata>)>,
+    stations: &GroundStations,
+    settings: &OpticalSettings,
+    start: DateTime<Utc>,
+    polar_motion: PolarMotion,
+) -> Result<Tonight, String> {
+    ComputeTaskPool::get_or_init(TaskPool::default);
+    let start_jd = julian_date(start);
+    let nights: Vec<Option<(f64, f64)>> = stations
+        .0
+        .iter()
+        .map(|station| next_night(station, settings, start_jd, polar_motion))
+        .collect();
+
+    let objects: Vec<(DebrisSat, String, f64)> = entries
+        .into_iter()
+        .map(|(entry, metadata)| {
+            let diameter = diameter_m(entry.name.as_deref(), metadata.as_ref());
+            let name = entry
+                .name
+                .clone()
+                .or_else(|| metadata.map(|m| m.name))
+                .unwrap_or_default();
+            let sat = DebrisSat {
+                name: entry.name,
+                elements: entry.elements,
+                satrec: entry.satrec,
+            };
+            (sat, name, diameter)
+        })
+        .collect();
+
+    let mut passes: Vec<(usize, usize, VisiblePass)> = objects
+        .par_splat_map(ComputeTaskPool::get(), None, |offset, chunk| {
+            let mut found = Vec::new();
+            for (i, (sat, _, diameter)) in chunk.iter().enumerate() {
+                let mut sat = sat.clone();
+                for (s, (station, night)) in stations.0.iter().zip(&nights).enumerate() {
+                    let Some(night) = *night else {
+                        continue;
+                    };
+                    for pass in predict_visible_passes(
+                        &mut sat,
+                        station,
+                        settings,
+                        *diameter,
+                        night,
+                        polar_motion,
+                    ) {
+                        found.push((offset + i, s, pass));
+                    }
+                }
+            }
+            found
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+    passes.sort_by(|a, b| a.2.start.jd.total_cmp(&b.2.start.jd));
+
+    let mut writer = csv::Writer::from_writer(std::io::stdout().lock());
+    let degrees = |radians: f64| format!("{:.1}", radians.to_degrees());
+    writer
+        .write_record([
+            "STATION",
+            "NORAD",
+            "NAME",
+            "START_UTC",
+            "HIGHEST_UTC",
+            "END_UTC",
+            "MAX_EL_DEG",
+            "START_AZ_DEG",
+            "HIGHEST_AZ_DEG",
+            "END_AZ_DEG",
+            "MAG",
+        ])
+        .map_err(|e| e.to_string())?;
+    for (object, station, pass) in &passes {
+        let (sat, name, _) = &objects[*object];
+        writer
+            .write_record([
+                stations.0[*station].name.clone(),
+                sat.elements.catalog_number.to_string(),
+                name.clone(),
+                time(pass.start.jd),
+                time(pass.highest.jd),
+                time(pass.end.jd),
+                degrees(pass.highest.look.elevation),
+                degrees(pass.start.look.azimuth),
+                degrees(pass.highest.look.azimuth),
+                degrees(pass.end.look.azimuth),
+                format!("{:.1}", pass.brightest),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(Tonight {
+        nights: nights
+            .iter()
+            .map(|night| night.map(|(dusk, dawn)| (datetime(dusk), datetime(dawn))))
+            .collect(),
+        passes: passes.len(),
+    })
+}
+
+fn datetime(jd_full: f64) -> DateTime<Utc> {
+    datetime_from_jd(jd_full, 0.0)
+}
+
+fn time(jd_full: f64) -> String {
+    datetime_from_jd(jd_full, 0.0)
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string()
+}
+
+/// Marker for the sky panel.
+#[derive(Component)]
+pub struct SkyPanel;
+
+/// Spawn the sky panel at the bottom, right of centre, when there are
+/// stations to list.
+pub fn setup_sky_panel(mut commands: Commands, stations: Res<GroundStations>) {
+    if stations.0.is_empty() {
+        return;
+    }
+    commands.spawn((
+        Name::new("Sky Panel"),
+        SkyPanel,
+        Text::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(64.0),
+            left: Val::Percent(62.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Interaction::default(),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    ));
+}
+
+/// List, per station, how many shown objects could be seen optically at
+/// the sim time and the brightest of them.
+pub fn update_sky_panel(
+    scene: SceneFrame,
+    sun: Res<Sun>,
+    stations: Res<GroundStations>,
+    settings: Res<OpticalSettings>,
+    debris_field: Res<DebrisField>,
+    debris: Query<(
+        &Debris,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&SatelliteMetadata>,
+    )>,
+    panel: Option<Single<&mut Text, With<SkyPanel>>>,
+) {
+    let Some(mut text) = panel else {
+        return;
+    };
+    let to_itrf = scene.itrf_to_display().inverse();
+    let sun = sun.itrf;
+
+    let mut lines = vec![format!(
+        "Optically visible (mag ≤ {:.1}):",
+        settings.limiting_magnitude
+    )];
+    for station in &stations.0 {
+        let sun_elevation = sun_elevation(station, sun).to_degrees();
+        if !settings.is_dark(station, sun) {
+            lines.push(format!(
+                "{}: too light (Sun {:.1}°, needs ≤ {:.0}°)",
+                station.name,
+                sun_elevation,
+                settings.twilight.to_degrees()
+            ));
+            continue;
+        }
+
+        let mut seen: Vec<(String, Sighting)> = debris
+            .iter()
+            .filter(|(_, _, visibility, _)| visibility.get())
+            .filter_map(|(debris, transform, _, metadata)| {
+                let sat = debris_field.sats.get(debris.sat_index)?;
+                let r = (to_itrf * transform.translation()).as_dvec3() / KM_TO_WORLD as f64;
+                let diameter = diameter_m(sat.name.as_deref(), metadata);
+                let sighting = settings.sighting(station, sun, r.into(), diameter)?;
+                let name = sat
+                    .name
+                    .clone()
+                    .or_else(|| metadata.map(|m| m.name.clone()))
+                    .unwrap_or_else(|| sat.elements.catalog_number.to_string());
+                Some((name, sighting))
+            })
+            .collect();
+        seen.sort_by(|a, b| a.1.magnitude.total_cmp(&b.1.magnitude));
+        lines.push(format!(
+            "{} (Sun {:.1}°): {}",
+            station.name,
+            sun_elevation,
+            seen.len()
+        ));
+        for (name, sighting) in seen.iter().take(PANEL_OBJECTS) {
+            lines.push(format!(
+                "  {}  mag {:.1}  az {:.0}°  el {:.0}°",
+                name,
+                sighting.magnitude,
+                sighting.look.azimuth.to_degrees(),
+                sighting.look.elevation.to_degrees()
+            ));
+        }
+    }
+    text.0 = lines.join("\n");
+}